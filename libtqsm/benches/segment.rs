@@ -0,0 +1,50 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const ASCII_TEXT: &str = "Dr. Smith arrived at 3 p.m. on Monday. He said: \"It's late!\" \
+    The meeting (see Fig. 2) lasted 1.5 hours. Was it worth it? Nobody knows.\n\n\
+    Mr. Jones, Ph.D., disagreed. The U.S. team left early. Then the rain began.";
+
+fn bench_segment(c: &mut Criterion) {
+    let ascii_text = ASCII_TEXT.repeat(50);
+    // The same text with a few accented letters, which takes the grapheme path
+    let non_ascii_text = ascii_text
+        .replace("Smith", "Smïth")
+        .replace("Jones", "Jönes");
+    // A carriage return in every paragraph sends the same ASCII text down the grapheme
+    // path, which all text used to take
+    let indexed_text = ascii_text
+        .replace("Nobody knows", "Nobody\rknows")
+        .replace("the rain", "the\rrain");
+    c.bench_function("segment ascii", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&ascii_text)).unwrap())
+    });
+    c.bench_function("segment ascii by grapheme", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&indexed_text)).unwrap())
+    });
+    c.bench_function("segment non-ascii", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&non_ascii_text)).unwrap())
+    });
+}
+
+/// Inputs that used to take quadratic time
+fn bench_pathological(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pathological");
+    group.sample_size(10);
+    let long_word = "Pneumonoultramicroscopicsilicovolcanoconiosis".repeat(20);
+    let long_words = format!("This is the word {}. ", long_word).repeat(100);
+    group.bench_function("long words before periods", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&long_words)).unwrap())
+    });
+    let blob = format!("Attached: {}. Done.", "QUJDRA+/".repeat(128 * 1024));
+    group.bench_function("long token without boundaries", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&blob)).unwrap())
+    });
+    let quotes_and_brackets = "“Go. Now.” (See p. 4. Or 5.) ".repeat(5000);
+    group.bench_function("many skippable ranges", |b| {
+        b.iter(|| libtqsm::segment("en", black_box(&quotes_and_brackets)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_segment, bench_pathological);
+criterion_main!(benches);
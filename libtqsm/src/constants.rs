@@ -2,8 +2,8 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 pub(crate) const LANGDATA_STR: &str = include_str!("data/langdata.json");
-pub(crate) const QUOTE_PAIRS_ARRAY: [(&str, &str); 12] = [
-    ("\"", "\""),
+/// Straight double quotes are paired separately, see `straight_quote_ranges`
+pub(crate) const QUOTE_PAIRS_ARRAY: [(&str, &str); 11] = [
     (" '", "'"), // Need a space before ' to avoid capturing don't , l'Avv etc
     ("«", "»"),
     ("‘", "’"),
@@ -16,6 +16,16 @@ pub(crate) const QUOTE_PAIRS_ARRAY: [(&str, &str); 12] = [
     ("《", "》"),
     ("「", "」"),
 ];
+pub(crate) const CLOSING_PUNCTUATION: [char; 11] =
+    ['"', '\'', ')', ']', '}', '»', '”', '’', '›', '」', '》'];
+/// Characters closing the bracketed spans that are kept within a sentence.
+pub(crate) const CLOSING_BRACKETS: [char; 4] = [')', ']', '}', '）'];
+/// Quote characters that also serve as letters inside a word,
+/// such as the ʻokina in `Hawai‘i` or elisions like `l’amour`.
+pub(crate) const WORD_INTERNAL_QUOTES: [char; 3] = ['‘', '’', '‛'];
+/// Spaces trimmed from both ends of a sentence, including the ideographic
+/// space used between sentences in CJK text.
+pub(crate) const SENTENCE_PADDING: [char; 2] = [' ', '\u{3000}'];
 pub(crate) const GLOBAL_SENTENCE_TERMINATORS: [char; 155] = [
     '\u{21}',
     '\u{2E}',
@@ -223,6 +233,7 @@ pub(crate) static LANGUAGE_FALLBACKS: Lazy<HashMap<&'static str, Vec<&'static st
             ("cdo", vec!["nan", "zh-hant"]),
             ("ce", vec!["ru"]),
             ("co", vec!["it"]),
+            ("ckb", vec!["ku"]),
             ("crh", vec!["crh-latn"]),
             ("crh-cyrl", vec!["ru"]),
             ("cs", vec!["sk"]),
@@ -234,6 +245,7 @@ pub(crate) static LANGUAGE_FALLBACKS: Lazy<HashMap<&'static str, Vec<&'static st
             ("dsb", vec!["de"]),
             ("dtp", vec!["ms"]),
             ("dty", vec!["ne"]),
+            ("dz", vec!["bo"]),
             ("egl", vec!["it"]),
             ("eml", vec!["it"]),
             ("en-ca", vec!["en"]),
@@ -242,6 +254,7 @@ pub(crate) static LANGUAGE_FALLBACKS: Lazy<HashMap<&'static str, Vec<&'static st
             ("es-formal", vec!["es"]),
             ("ext", vec!["es"]),
             ("ff", vec!["fr"]),
+            ("fil", vec!["tl"]),
             ("fit", vec!["fi"]),
             ("frc", vec!["fr"]),
             ("frp", vec!["fr"]),
@@ -252,7 +265,6 @@ pub(crate) static LANGUAGE_FALLBACKS: Lazy<HashMap<&'static str, Vec<&'static st
             ("gan-hans", vec!["zh-hans"]),
             ("gan-hant", vec!["zh-hant", "zh-hans"]),
             ("gcr", vec!["fr"]),
-            ("gl", vec!["pt"]),
             ("glk", vec!["fa"]),
             ("gn", vec!["es"]),
             ("gom", vec!["gom-deva"]),
@@ -294,8 +306,8 @@ pub(crate) static LANGUAGE_FALLBACKS: Lazy<HashMap<&'static str, Vec<&'static st
             ("krl", vec!["fi"]),
             ("ks", vec!["ks-arab"]),
             ("ksh", vec!["de"]),
-            ("ku", vec!["ku-latn"]),
             ("ku-arab", vec!["ckb"]),
+            ("ku-latn", vec!["ku"]),
             ("kum", vec!["ru"]),
             ("kv", vec!["ru"]),
             ("lad", vec!["es"]),
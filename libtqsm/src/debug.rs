@@ -0,0 +1,32 @@
+use crate::LANGUAGE_REGISTRY;
+use std::collections::HashMap;
+
+/// Segments `text` with every supported language, keyed by language code, to compare
+/// how they split it when choosing a language or debugging one.
+pub fn segment_all(text: impl AsRef<str>) -> HashMap<&'static str, Vec<String>> {
+    let text = text.as_ref();
+    LANGUAGE_REGISTRY
+        .iter()
+        .map(|(lang_code, language)| (*lang_code, language.segment(text)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::languages::SUPPORTED_LANGUAGES;
+
+    #[test]
+    fn test_segment_all() {
+        let text = "Dr. Smith arrived. He sat down.";
+        let by_language = segment_all(text);
+        assert_eq!(by_language.len(), SUPPORTED_LANGUAGES.len());
+        for language in SUPPORTED_LANGUAGES {
+            assert!(by_language.contains_key(language.language_code()));
+        }
+        assert_eq!(
+            by_language["en"],
+            vec!["Dr. Smith arrived.", "He sat down."]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{dominant_script, resolve_language, Language, Script};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use whatlang::Lang;
+
+/// whatlang's own reliability check is too strict for a single sentence
+const MIN_CONFIDENCE: f64 = 0.5;
+
+static NO_WORDS: Lazy<HashSet<&'static str>> = Lazy::new(HashSet::new);
+
+/// Splits on the global sentence terminators only, without any language's
+/// abbreviations, so that text in any language can be split before detection.
+struct NeutralLanguage;
+impl Language for NeutralLanguage {
+    fn language_code(&self) -> &'static str {
+        "und"
+    }
+    fn abbreviation_char(&self) -> &'static str {
+        ""
+    }
+    fn abbreviations(&self) -> &'static HashSet<&'static str> {
+        &NO_WORDS
+    }
+    fn max_abbreviation_chars(&self) -> usize {
+        0
+    }
+    fn exclamation_words(&self) -> &'static HashSet<&'static str> {
+        &NO_WORDS
+    }
+}
+
+/// Detects the language of `text`, returning the code of the supported language
+/// that handles it, or `None` if no language could be identified with confidence.
+/// Text too short to identify is still recognized by a script used by a single
+/// supported language.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    whatlang::detect(text)
+        .filter(|info| info.is_reliable() || info.confidence() >= MIN_CONFIDENCE)
+        .and_then(|info| resolve_language(iso_639_1(info.lang())))
+        .or_else(|| dominant_script(text).and_then(script_language))
+}
+
+/// Returns the supported language written in `script`, if it is the only one.
+fn script_language(script: Script) -> Option<&'static str> {
+    let lang_code = match script {
+        Script::Greek => "el",
+        Script::Armenian => "hy",
+        Script::Bengali => "bn",
+        Script::Gurmukhi => "pa",
+        Script::Gujarati => "gu",
+        Script::Oriya => "or",
+        Script::Tamil => "ta",
+        Script::Telugu => "te",
+        Script::Kannada => "kn",
+        Script::Malayalam => "ml",
+        Script::Tibetan => "bo",
+        Script::Myanmar => "my",
+        _ => return None,
+    };
+    resolve_language(lang_code)
+}
+
+/// Segments a document mixing several languages, labeling each sentence with its
+/// detected language. Sentences too short to identify take the language of the
+/// previous sentence, or of the whole document.
+pub fn segment_multilang(text: &str) -> Result<Vec<(String, &'static str)>> {
+    let mut current_language = detect_language(text).unwrap_or("en");
+    Ok(NeutralLanguage
+        .segment(text)
+        .into_iter()
+        .map(|sentence| {
+            if let Some(language) = detect_language(&sentence) {
+                current_language = language;
+            }
+            (sentence, current_language)
+        })
+        .collect())
+}
+
+fn iso_639_1(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Afr => "af",
+        Lang::Aka => "ak",
+        Lang::Amh => "am",
+        Lang::Ara => "ar",
+        Lang::Aze => "az",
+        Lang::Bel => "be",
+        Lang::Ben => "bn",
+        Lang::Bul => "bg",
+        Lang::Cat => "ca",
+        Lang::Ces => "cs",
+        Lang::Cmn => "zh",
+        Lang::Dan => "da",
+        Lang::Deu => "de",
+        Lang::Ell => "el",
+        Lang::Eng => "en",
+        Lang::Epo => "eo",
+        Lang::Est => "et",
+        Lang::Fin => "fi",
+        Lang::Fra => "fr",
+        Lang::Guj => "gu",
+        Lang::Heb => "he",
+        Lang::Hin => "hi",
+        Lang::Hrv => "hr",
+        Lang::Hun => "hu",
+        Lang::Hye => "hy",
+        Lang::Ind => "id",
+        Lang::Ita => "it",
+        Lang::Jav => "jv",
+        Lang::Jpn => "ja",
+        Lang::Kan => "kn",
+        Lang::Kat => "ka",
+        Lang::Khm => "km",
+        Lang::Kor => "ko",
+        Lang::Lat => "la",
+        Lang::Lav => "lv",
+        Lang::Lit => "lt",
+        Lang::Mal => "ml",
+        Lang::Mar => "mr",
+        Lang::Mkd => "mk",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Nld => "nl",
+        Lang::Nob => "nb",
+        Lang::Ori => "or",
+        Lang::Pan => "pa",
+        Lang::Pes => "fa",
+        Lang::Pol => "pl",
+        Lang::Por => "pt",
+        Lang::Ron => "ro",
+        Lang::Rus => "ru",
+        Lang::Sin => "si",
+        Lang::Slk => "sk",
+        Lang::Slv => "sl",
+        Lang::Sna => "sn",
+        Lang::Spa => "es",
+        Lang::Srp => "sr",
+        Lang::Swe => "sv",
+        Lang::Tam => "ta",
+        Lang::Tel => "te",
+        Lang::Tgl => "tl",
+        Lang::Tha => "th",
+        Lang::Tuk => "tk",
+        Lang::Tur => "tr",
+        Lang::Ukr => "uk",
+        Lang::Urd => "ur",
+        Lang::Uzb => "uz",
+        Lang::Vie => "vi",
+        Lang::Yid => "yi",
+        Lang::Zul => "zu",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segment_multilang() -> Result<()> {
+        let text = "The weather was lovely this morning in the city. \
+                    ذهبت إلى المدرسة في الصباح الباكر مع أصدقائي. \
+                    We walked along the river until the sun went down.";
+        let sents = segment_multilang(text)?;
+        let languages: Vec<&str> = sents.iter().map(|(_, language)| *language).collect();
+        assert_eq!(languages, vec!["en", "ar", "en"]);
+        assert_eq!(sents[1].0, "ذهبت إلى المدرسة في الصباح الباكر مع أصدقائي.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_by_script() {
+        assert_eq!(detect_language("Ναι."), Some("el"));
+        assert_eq!(detect_language("Այո։"), Some("hy"));
+        assert_eq!(detect_language("?!"), None);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::find_language;
+use anyhow::Result;
+use std::ops::Range;
+use tl::{Node, NodeHandle, Parser};
+
+const BLOCK_ELEMENTS: [&str; 34] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "caption",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+];
+const INVISIBLE_ELEMENTS: [&str; 6] = ["head", "noscript", "script", "style", "template", "title"];
+
+/// Segments the visible text of an HTML document.
+///
+/// Returns each sentence along with the byte range it covers in `html`,
+/// including any inline markup inside the sentence.
+/// Block-level elements are treated as paragraph breaks.
+pub fn segment_html(lang_code: &str, html: &str) -> Result<Vec<(String, Range<usize>)>> {
+    let language = find_language(lang_code)?;
+    let dom = tl::parse(html, tl::ParserOptions::default())?;
+    let mut extractor = TextExtractor::new(html);
+    for handle in dom.children() {
+        extractor.visit(*handle, dom.parser());
+    }
+
+    let mut sentences = Vec::new();
+    for (start, end) in language.segment_spans(&extractor.text) {
+        let sentence = extractor.text[start..end].trim();
+        if sentence.is_empty() {
+            continue;
+        }
+        let start = start + extractor.text[start..end].find(sentence).unwrap_or(0);
+        let end = start + sentence.len();
+        sentences.push((
+            sentence.to_string(),
+            extractor.source_offset(start, false)..extractor.source_offset(end, true),
+        ));
+    }
+    Ok(sentences)
+}
+
+/// A run of text copied verbatim from the HTML source.
+struct TextChunk {
+    text_start: usize,
+    source_start: usize,
+    len: usize,
+}
+
+struct TextExtractor<'a> {
+    source: &'a str,
+    text: String,
+    chunks: Vec<TextChunk>,
+}
+
+impl<'a> TextExtractor<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            text: String::new(),
+            chunks: Vec::new(),
+        }
+    }
+
+    fn visit(&mut self, handle: NodeHandle, parser: &Parser<'a>) {
+        match handle.get(parser) {
+            Some(Node::Raw(raw)) => {
+                if let Some(raw_text) = raw.try_as_utf8_str() {
+                    let source_start = raw.as_ptr() as usize - self.source.as_ptr() as usize;
+                    self.chunks.push(TextChunk {
+                        text_start: self.text.len(),
+                        source_start,
+                        len: raw_text.len(),
+                    });
+                    self.text.push_str(raw_text);
+                }
+            }
+            Some(Node::Tag(tag)) => {
+                let name = tag.name().as_utf8_str().to_lowercase();
+                if INVISIBLE_ELEMENTS.contains(&name.as_str()) {
+                    return;
+                }
+                if name == "br" {
+                    self.text.push('\n');
+                    return;
+                }
+                let is_block = BLOCK_ELEMENTS.contains(&name.as_str());
+                if is_block {
+                    self.push_paragraph_break();
+                }
+                for child in tag.children().top().iter() {
+                    self.visit(*child, parser);
+                }
+                if is_block {
+                    self.push_paragraph_break();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn push_paragraph_break(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with("\n\n") {
+            self.text.push_str("\n\n");
+        }
+    }
+
+    /// Maps an offset in the extracted text back to the HTML source.
+    /// When `is_end` is set, an offset at the end of a chunk maps to the end of that chunk
+    /// rather than the start of the next one.
+    fn source_offset(&self, text_offset: usize, is_end: bool) -> usize {
+        let idx = self.chunks.partition_point(|chunk| {
+            if is_end {
+                chunk.text_start + chunk.len < text_offset
+            } else {
+                chunk.text_start + chunk.len <= text_offset
+            }
+        });
+        match self.chunks.get(idx) {
+            Some(chunk) => chunk.source_start + (text_offset - chunk.text_start),
+            None => self.source.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segment_html() -> Result<()> {
+        let html = "<html><head><title>Ignored.</title><style>p { color: red; }</style></head>\
+            <body><h1>Welcome</h1><p>This is <b>bold</b>. Another one!</p>\
+            <div>Last sentence</div></body></html>";
+        let sents = segment_html("en", html)?;
+        let texts: Vec<&str> = sents.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["Welcome", "This is bold.", "Another one!", "Last sentence"]
+        );
+        let sources: Vec<&str> = sents
+            .iter()
+            .map(|(_, range)| &html[range.clone()])
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                "Welcome",
+                "This is <b>bold</b>.",
+                "Another one!",
+                "Last sentence"
+            ]
+        );
+        Ok(())
+    }
+}
@@ -1,32 +1,42 @@
-use crate::{Language, GLOBAL_SENTENCE_TERMINATORS, WORD_SPLIT_REGEX};
+use crate::{last_word, Language, GLOBAL_SENTENCE_TERMINATORS, QUOTE_PAIRS_ARRAY};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use unicode_segmentation::UnicodeSegmentation;
 
-const N_BASE_LANGUAGES: usize = 30;
+const N_BASE_LANGUAGES: usize = 41;
 pub(crate) const SUPPORTED_LANGUAGES: [&(dyn Language + Send + Sync + 'static); N_BASE_LANGUAGES] = [
     &AmLanguage,
     &ArLanguage,
+    &AzLanguage,
     &BgLanguage,
     &BnLanguage,
+    &BoLanguage,
     &CaLanguage,
     &EnLanguage,
     &ElLanguage,
     &DaLanguage,
     &DeLanguage,
     &EsLanguage,
+    &EuLanguage,
     &FiLanguage,
+    &FoLanguage,
     &FrLanguage,
+    &GlLanguage,
     &GuLanguage,
+    &HaLanguage,
     &HiLanguage,
     &HyLanguage,
+    &IsLanguage,
     &ItLanguage,
     &KkLanguage,
     &KnLanguage,
+    &KuLanguage,
     &MlLanguage,
     &MrLanguage,
+    &MtLanguage,
     &MyLanguage,
     &NlLanguage,
+    &OmLanguage,
     &OrLanguage,
     &PaLanguage,
     &SkLanguage,
@@ -35,6 +45,11 @@ pub(crate) const SUPPORTED_LANGUAGES: [&(dyn Language + Send + Sync + 'static);
     &RuLanguage,
     &TaLanguage,
     &TeLanguage,
+    &TlLanguage,
+];
+const AZ_MONTHS: [&str; 12] = [
+    "yanvar", "fevral", "mart", "aprel", "may", "iyun", "iyul", "avqust", "sentyabr", "oktyabr",
+    "noyabr", "dekabr",
 ];
 const DE_MONTHS: [&str; 12] = [
     "Januar",
@@ -90,9 +105,51 @@ const SK_MONTHS: [&str; 24] = [
     "Novembra",
     "Decembra",
 ];
-static RU_CNW: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[0-9a-zа-я]").unwrap());
+/// English abbreviations that may also close a sentence
+const EN_SENTENCE_FINAL_ABBREVIATIONS: [&str; 9] = [
+    "u.s", "u.k", "u.n", "e.u", "etc", "inc", "ltd", "co", "corp",
+];
+/// Words that, capitalized, almost always start a new sentence
+const EN_SENTENCE_STARTERS: [&str; 24] = [
+    "A", "An", "And", "But", "He", "Her", "His", "However", "I", "In", "It", "Its", "Our", "She",
+    "So", "That", "The", "Their", "Then", "There", "These", "They", "This", "We",
+];
+/// Abbreviations closing a list, which end the sentence too before a capitalized word
+const RU_SENTENCE_FINAL_ABBREVIATIONS: [&str; 3] = ["др", "т.д", "т.п"];
+/// English text has no use for the CJK corner and title brackets
+static EN_QUOTE_PAIRS: Lazy<Vec<(&str, &str)>> = Lazy::new(|| {
+    QUOTE_PAIRS_ARRAY
+        .into_iter()
+        .filter(|(opening, _)| !["《", "「"].contains(opening))
+        .collect()
+});
+/// German also uses guillemets pointing inwards: »Zitat« and ›Zitat‹
+static DE_QUOTE_PAIRS: Lazy<Vec<(&str, &str)>> = Lazy::new(|| {
+    QUOTE_PAIRS_ARRAY
+        .into_iter()
+        .chain([("»", "«"), ("›", "‹")])
+        .collect()
+});
+/// Also lets a lowercase word after a space continue, as after an ordinal
+/// (`1. января`) or an abbreviation (`т.е. нужно`)
+static RU_CNW: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zа-яё]").unwrap());
+/// An elided article or pronoun (`l'`, `d'`, `s'`) at the start of a Catalan word
+static CA_ELISION_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?i:[dlmnst])['’]").unwrap());
 static CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-z]").unwrap());
 static KK_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zа-я]").unwrap());
+static FO_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-záðíóúýæø]").unwrap());
+static IS_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-záðéíóúýþæö]").unwrap());
+static KU_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zçêîşû]").unwrap());
+static AZ_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zçəğıöşü]").unwrap());
+static GL_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-záéíóúñü]").unwrap());
+static EU_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zçñü]").unwrap());
+/// Hausa adds the hooked letters ɓ, ɗ, ƙ and ƴ
+static HA_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zɓɗƙƴ]").unwrap());
+/// Filipino adds ñ from Spanish
+static TL_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zñ]").unwrap());
+static MT_CNW_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\W*[0-9a-zàèìòùċġħż]").unwrap());
+/// The ano teleia (`·`) is a semicolon, also typed as the Latin-1 middle dot
+const EL_NEVER_BREAK_CHARACTERS: [char; 2] = ['\u{387}', '\u{b7}'];
 static EL_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     let regex_str = format!(
         r"[{}]+",
@@ -104,6 +161,11 @@ static EL_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     );
     Regex::new(&regex_str).unwrap()
 });
+/// The emphasis, exclamation, and question marks (`՛`, `՜`, `՞`) sit over the stressed
+/// vowel inside a word, and `՝` is a comma
+const HY_NEVER_BREAK_CHARACTERS: [char; 4] = ['՛', '՜', '՝', '՞'];
+/// Armenian ends sentences with `։` (or `:`), leaving `.` to abbreviations and borrowed
+/// terms.
 static HY_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     let regex_str = format!(
         r"[{}]+",
@@ -111,7 +173,7 @@ static HY_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
             GLOBAL_SENTENCE_TERMINATORS
                 .into_iter()
                 .filter(|c| *c != '.')
-                .chain(['։', '՜', ':'].into_iter())
+                .chain(['։', ':'].into_iter())
         )
     );
     Regex::new(&regex_str).unwrap()
@@ -128,6 +190,40 @@ static MY_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(&regex_str).unwrap()
 });
 
+/// Tibetan ends sentences with the shad marks; the tsheg `་` only separates syllables.
+static BO_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    let regex_str = format!(
+        r"[{}]+",
+        String::from_iter(
+            GLOBAL_SENTENCE_TERMINATORS
+                .into_iter()
+                .chain(['།', '༎', '༏', '༐', '༑'].into_iter())
+        )
+    );
+    Regex::new(&regex_str).unwrap()
+});
+
+/// Lowercases `text` using Turkic rules, where `İ` lowers to `i` and `I` to `ı`.
+fn to_turkic_lowercase(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'İ' => "i".to_string(),
+            'I' => "ı".to_string(),
+            c => c.to_lowercase().to_string(),
+        })
+        .collect()
+}
+
+/// Uppercases `text` using Turkic rules, where `i` uppers to `İ` and `ı` to `I`.
+fn to_turkic_uppercase(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'i' => "İ".to_string(),
+            c => c.to_uppercase().to_string(),
+        })
+        .collect()
+}
+
 fn to_title_case(text: &str) -> String {
     let mut out = String::with_capacity(text.len());
     let mut graphemes = text.graphemes(false);
@@ -152,6 +248,34 @@ impl Language for ArLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct AzLanguage;
+impl Language for AzLanguage {
+    fn language_code(&self) -> &'static str {
+        "az"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        if AZ_CNW_REGEX.is_match(text_after_boundary) {
+            return true;
+        }
+        // Ordinal dates such as `28. May 1918`
+        match text_after_boundary.trim().split_word_bounds().next() {
+            Some(word) if !word.is_empty() => {
+                AZ_MONTHS.contains(&to_turkic_lowercase(word).as_str())
+            }
+            _ => false,
+        }
+    }
+    fn matches_abbreviation(&self, word: &str) -> bool {
+        let lowercase_word = to_turkic_lowercase(word);
+        self.abbreviations().contains(word)
+            || self.abbreviations().contains(lowercase_word.as_str())
+            || self
+                .abbreviations()
+                .contains(to_turkic_uppercase(word).as_str())
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct BgLanguage;
 impl Language for BgLanguage {
@@ -168,12 +292,30 @@ impl Language for BnLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct BoLanguage;
+impl Language for BoLanguage {
+    fn language_code(&self) -> &'static str {
+        "bo"
+    }
+    fn sentence_break_regex(&self) -> &Regex {
+        &BO_SENTENCE_BOUNDARY_REGEX
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct EnLanguage;
 impl Language for EnLanguage {
     fn language_code(&self) -> &'static str {
         "en"
     }
+    fn quote_pairs(&self) -> &'static [(&'static str, &'static str)] {
+        &EN_QUOTE_PAIRS
+    }
+    fn ends_sentence_after_abbreviation(&self, abbreviation: &str, next_word: &str) -> bool {
+        EN_SENTENCE_FINAL_ABBREVIATIONS.contains(&abbreviation.to_lowercase().as_str())
+            && EN_SENTENCE_STARTERS.contains(&next_word)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -183,12 +325,32 @@ impl Language for EsLanguage {
         "es"
     }
 }
+
+#[derive(Clone, Default)]
+pub(crate) struct EuLanguage;
+impl Language for EuLanguage {
+    fn language_code(&self) -> &'static str {
+        "eu"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        EU_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct CaLanguage;
 impl Language for CaLanguage {
     fn language_code(&self) -> &'static str {
         "ca"
     }
+    fn get_lastword<'a>(&'a self, text: &'a str) -> Option<&'a str> {
+        let last_word = last_word(text);
+        Some(
+            CA_ELISION_REGEX
+                .find(last_word)
+                .map_or(last_word, |elision| &last_word[elision.end()..]),
+        )
+    }
 }
 
 #[derive(Clone, Default)]
@@ -224,6 +386,10 @@ impl Language for RuLanguage {
     fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
         RU_CNW.is_match(text_after_boundary)
     }
+    fn ends_sentence_after_abbreviation(&self, abbreviation: &str, next_word: &str) -> bool {
+        RU_SENTENCE_FINAL_ABBREVIATIONS.contains(&abbreviation.to_lowercase().as_str())
+            && next_word.starts_with(char::is_uppercase)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -232,6 +398,9 @@ impl Language for DeLanguage {
     fn language_code(&self) -> &'static str {
         "de"
     }
+    fn quote_pairs(&self) -> &'static [(&'static str, &'static str)] {
+        &DE_QUOTE_PAIRS
+    }
     fn is_punctuation_between_quotes(&self) -> bool {
         true
     }
@@ -278,6 +447,9 @@ impl Language for ElLanguage {
     fn sentence_break_regex(&self) -> &Regex {
         &EL_SENTENCE_BOUNDARY_REGEX
     }
+    fn never_break_characters(&self) -> &'static [char] {
+        &EL_NEVER_BREAK_CHARACTERS
+    }
 }
 
 #[derive(Clone, Default)]
@@ -310,6 +482,28 @@ impl Language for FiLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct FoLanguage;
+impl Language for FoLanguage {
+    fn language_code(&self) -> &'static str {
+        "fo"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        FO_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct GlLanguage;
+impl Language for GlLanguage {
+    fn language_code(&self) -> &'static str {
+        "gl"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        GL_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct GuLanguage;
 impl Language for GuLanguage {
@@ -318,6 +512,17 @@ impl Language for GuLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct HaLanguage;
+impl Language for HaLanguage {
+    fn language_code(&self) -> &'static str {
+        "ha"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        HA_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct HiLanguage;
 impl Language for HiLanguage {
@@ -333,6 +538,17 @@ impl Language for MrLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct MtLanguage;
+impl Language for MtLanguage {
+    fn language_code(&self) -> &'static str {
+        "mt"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        MT_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct HyLanguage;
 impl Language for HyLanguage {
@@ -342,6 +558,20 @@ impl Language for HyLanguage {
     fn sentence_break_regex(&self) -> &Regex {
         &HY_SENTENCE_BOUNDARY_REGEX
     }
+    fn never_break_characters(&self) -> &'static [char] {
+        &HY_NEVER_BREAK_CHARACTERS
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct IsLanguage;
+impl Language for IsLanguage {
+    fn language_code(&self) -> &'static str {
+        "is"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        IS_CNW_REGEX.is_match(text_after_boundary)
+    }
 }
 
 #[derive(Clone, Default)]
@@ -351,7 +581,7 @@ impl Language for ItLanguage {
         "it"
     }
     fn get_lastword<'a>(&'a self, text: &'a str) -> Option<&'a str> {
-        let last_word = WORD_SPLIT_REGEX.split(text).last()?;
+        let last_word = last_word(text);
         last_word.split("l'").last()
     }
 }
@@ -375,6 +605,19 @@ impl Language for KnLanguage {
     }
 }
 
+/// Kurdish, written in Latin (Kurmanji) and Arabic (Sorani) scripts.
+/// Sorani terminators such as `؟` and `۔` are part of the global terminator set.
+#[derive(Clone, Default)]
+pub(crate) struct KuLanguage;
+impl Language for KuLanguage {
+    fn language_code(&self) -> &'static str {
+        "ku"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        KU_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct MlLanguage;
 impl Language for MlLanguage {
@@ -402,6 +645,17 @@ impl Language for NlLanguage {
     }
 }
 
+#[derive(Clone, Default)]
+pub(crate) struct OmLanguage;
+impl Language for OmLanguage {
+    fn language_code(&self) -> &'static str {
+        "om"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        CNW_REGEX.is_match(text_after_boundary)
+    }
+}
+
 #[derive(Clone, Default)]
 pub(crate) struct OrLanguage;
 impl Language for OrLanguage {
@@ -463,3 +717,14 @@ impl Language for TeLanguage {
         "te"
     }
 }
+
+#[derive(Clone, Default)]
+pub(crate) struct TlLanguage;
+impl Language for TlLanguage {
+    fn language_code(&self) -> &'static str {
+        "tl"
+    }
+    fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
+        TL_CNW_REGEX.is_match(text_after_boundary)
+    }
+}
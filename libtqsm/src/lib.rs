@@ -1,17 +1,46 @@
 use anyhow::{bail, Result};
 use once_cell::sync::Lazy;
 use regex::{Match, Matches, Regex};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
+use std::ops::{ControlFlow, Range};
+use std::sync::{Arc, RwLock};
 use unicode_segmentation::UnicodeSegmentation;
 
 mod languages;
 use languages::SUPPORTED_LANGUAGES;
 
+mod options;
+pub use options::{SegmentationProfile, SegmenterOptions, TrimMode};
+
 mod constants;
+mod normalize;
+pub use normalize::{normalize_text, NormalizeOptions};
+mod script;
+pub use script::{dominant_script, Script};
+mod stream;
+pub use stream::{
+    segment_lines, segment_lines_with_options, segment_sentences_with_indices_iter,
+    segment_streaming, StreamingSegmenter,
+};
+
+#[cfg(feature = "detect")]
+mod detect;
+#[cfg(feature = "detect")]
+pub use detect::{detect_language, segment_multilang};
+#[cfg(feature = "debug-tools")]
+mod debug;
+#[cfg(feature = "html")]
+mod html;
 pub(crate) use constants::{
-    GLOBAL_SENTENCE_TERMINATORS, LANGDATA_STR, LANGUAGE_FALLBACKS, QUOTE_PAIRS_ARRAY,
+    CLOSING_BRACKETS, CLOSING_PUNCTUATION, GLOBAL_SENTENCE_TERMINATORS, LANGDATA_STR,
+    LANGUAGE_FALLBACKS, QUOTE_PAIRS_ARRAY, SENTENCE_PADDING, WORD_INTERNAL_QUOTES,
 };
+#[cfg(feature = "debug-tools")]
+pub use debug::segment_all;
+#[cfg(feature = "html")]
+pub use html::segment_html;
 
 pub static LANGUAGE_REGISTRY: Lazy<HashMap<&'static str, &(dyn Language + Send + Sync + 'static)>> =
     Lazy::new(|| {
@@ -20,15 +49,27 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<&'static str, &(dyn Language + Send +
             .map(|l| (l.language_code(), l))
             .collect()
     });
-static LANGDATA: Lazy<HashMap<&'static str, LanguageData>> =
-    Lazy::new(|| serde_json::from_str(LANGDATA_STR).unwrap());
-static QUOTE_PAIRS_REGEX: Lazy<Regex> = Lazy::new(|| {
-    let quotes_regx_str = QUOTE_PAIRS_ARRAY
-        .into_iter()
-        .map(|(left, right)| format!(r"{}(\n|.)*?{}", left, right))
-        .collect::<Vec<String>>()
-        .join("|");
-    Regex::new(&quotes_regx_str).unwrap()
+static LANGDATA: Lazy<HashMap<&'static str, LanguageData>> = Lazy::new(|| {
+    let mut langdata: HashMap<&'static str, LanguageData> =
+        serde_json::from_str(LANGDATA_STR).unwrap();
+    for data in langdata.values_mut() {
+        data.max_abbreviation_chars = data
+            .abbreviations
+            .iter()
+            .map(|abbrev| abbrev.chars().count())
+            .max()
+            .unwrap_or(0);
+    }
+    langdata
+});
+/// The regexes built so far for each distinct `quote_pairs` slice
+#[allow(clippy::type_complexity)]
+static QUOTE_PAIRS_REGEXES: Lazy<
+    RwLock<HashMap<&'static [(&'static str, &'static str)], &'static Regex>>,
+> = Lazy::new(Default::default);
+/// Iteration marks, the prolonged sound mark and small kana, which never start a word
+static WORD_CONTINUATION_MARK_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[々〻ゝゞヽヾーぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶㇰ-ㇿ]").unwrap()
 });
 static PARENS_REGEX: Lazy<fancy_regex::Regex> =
     Lazy::new(|| fancy_regex::Regex::new(r"([\(（<{\[])(?:\\\1|.)*?[\)\]}）]").unwrap());
@@ -36,9 +77,54 @@ static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| {
     let email_regex_str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Z|a-z]{2,7}";
     Regex::new(email_regex_str).unwrap()
 });
-static CONSECUTIVE_NEWLINES_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\n{2,})").unwrap());
+static SOCIAL_TOKEN_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[^\w@#])(@\w+(?:[.\-]\w+)*|#\w+)").unwrap());
+/// Inline and display math: `$...$`, `$$...$$`, `\(...\)` and `\[...\]`. An escaped
+/// dollar sign (`\$`) is matched on its own so that it can't open a formula. As in
+/// pandoc, inline math can't start or end with a space, so prices (`$5 and $10`) aren't
+/// taken for it.
+static LATEX_MATH_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"(?s)\\\$|\$\$.+?\$\$",
+        r"|\$(?:\\.|[^\s$\\])(?:(?:\\.|[^$\\])*(?:\\.|[^\s$\\]))?\$",
+        r"|\\\(.+?\\\)|\\\[.+?\\\]"
+    ))
+    .unwrap()
+});
+/// Dotted version numbers (`v1.2.3`, `5.3.RELEASE`), IPv4 and IPv6 addresses
+static TECHNICAL_TOKEN_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        // A capitalized word after a period starts the next sentence instead
+        r"\b(?:v\d+|\d+\.\d+)(?:\.\d+)*",
+        r"(?:[\-+][0-9A-Za-z]+|\.(?:[0-9a-z][0-9A-Za-z]*|[A-Z](?:[0-9A-Z][0-9A-Za-z]*)?\b))*",
+        r"|[0-9A-Fa-f]*:[0-9A-Fa-f]*:[0-9A-Fa-f:.]*[0-9A-Fa-f]"
+    ))
+    .unwrap()
+});
+/// Paragraph breaks: blank lines, form feeds, and the Unicode line/paragraph separators
+static CONSECUTIVE_NEWLINES_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(\n*[\x0C\x{2028}\x{2029}][\n\x0C\x{2028}\x{2029}]*|\n{2,})").unwrap()
+});
+/// Paragraph breaks when every line is a paragraph: any run of line breaks
+static LINE_BREAKS_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:\r?[\n\x0C\x{2028}\x{2029}])+").unwrap());
+/// Paragraph breaks, or a line made only of three or more punctuation marks or symbols
+/// (e.g. `***` or `- - -`) along with the line breaks around it
+static DIVIDER_OR_CONSECUTIVE_NEWLINES_REGEX: Lazy<Regex> =
+    Lazy::new(|| divider_or(&CONSECUTIVE_NEWLINES_REGEX));
+static DIVIDER_OR_LINE_BREAKS_REGEX: Lazy<Regex> = Lazy::new(|| divider_or(&LINE_BREAKS_REGEX));
+static NEWLINE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n").unwrap());
 static NUMBERED_REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\[\d+])+").unwrap());
-pub(crate) static WORD_SPLIT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\s\.]+").unwrap());
+static TRAILING_NUMBERED_REFERENCE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\[\d+])+$").unwrap());
+static ANY_NUMBERED_REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\[\d+])+").unwrap());
+/// A bullet, or a number or letter followed by `.` or `)`, opening a list item
+static LIST_MARKER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[^\S\n]*(?:[-*•‣◦–]|(?:\d{1,3}|[A-Za-z])[.)])[^\S\n]").unwrap());
+/// A numbered legal reference, such as `§ 5`, `¶ 2` or `Art. 3a`, ending the text
+static LEGAL_REFERENCE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:§§?|¶|\b(?i:art|sec|para)\.)[^\S\n]*\d+[a-z]?$").unwrap());
+static WORD_SPLIT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\s\.]+").unwrap());
 static GLOBAL_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(&format!(
         r"[{}]+",
@@ -47,29 +133,292 @@ static GLOBAL_SENTENCE_BOUNDARY_REGEX: Lazy<Regex> = Lazy::new(|| {
     .unwrap()
 });
 
-pub fn segment(lang_code: &str, text: &str) -> Result<Vec<String>> {
-    let language = match get_language(lang_code) {
-        Some(language) => language,
-        None => bail!("Language `{}` not supported", lang_code),
-    };
-    Ok(language.segment(text))
+pub fn segment(lang_code: &str, text: impl AsRef<str>) -> Result<Vec<String>> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment(text.as_ref()))
 }
 
-fn get_language(lang_code: &str) -> Option<&(dyn Language + Send + Sync + 'static)> {
-    let mut ret_lang = LANGUAGE_REGISTRY.get(lang_code).copied();
-    if ret_lang.is_none() {
-        let fallbacks = LANGUAGE_FALLBACKS
-            .get(lang_code)
-            .cloned()
-            .unwrap_or(["en"].to_vec());
-        for fallback_language in fallbacks {
-            ret_lang = get_language(fallback_language);
-            if ret_lang.is_some() {
-                break;
+pub fn segment_with_options(
+    lang_code: &str,
+    text: impl AsRef<str>,
+    options: &SegmenterOptions,
+) -> Result<Vec<String>> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_with_options(text.as_ref(), options))
+}
+
+/// Segments `text` into reference-counted sentences, so that handing them to several
+/// consumers clones a pointer rather than the text.
+pub fn segment_shared(lang_code: &str, text: impl AsRef<str>) -> Result<Vec<Arc<str>>> {
+    let language = find_language(lang_code)?;
+    let text = text.as_ref();
+    Ok(language
+        .segment_spans(text)
+        .into_iter()
+        .map(|(start, end)| Arc::from(&text[start..end]))
+        .collect())
+}
+
+/// Segments `text`, returning the byte start and end of each sentence along with it.
+/// Paragraph separators such as `"\n\n"` are included, at their place in `text`.
+pub fn segment_with_offsets(
+    lang_code: &str,
+    text: impl AsRef<str>,
+) -> Result<Vec<(usize, usize, String)>> {
+    let language = find_language(lang_code)?;
+    let text = text.as_ref();
+    Ok(language
+        .segment_spans(text)
+        .into_iter()
+        .map(|(start, end)| (start, end, text[start..end].to_string()))
+        .collect())
+}
+
+/// Returns the byte offset where each sentence of `text` begins, in source order.
+/// The first one is 0, and each sentence runs to the next offset (or the end of the
+/// text), whitespace included.
+pub fn boundary_offsets(lang_code: &str, text: impl AsRef<str>) -> Result<Vec<usize>> {
+    let language = find_language(lang_code)?;
+    Ok(language.boundary_offsets(text.as_ref()))
+}
+
+/// Segments `text`, returning each sentence with its location, terminator, and
+/// script, in source order.
+pub fn segment_detailed(lang_code: &str, text: impl AsRef<str>) -> Result<Vec<Sentence>> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_detailed(text.as_ref()))
+}
+
+/// Re-segments `text` after an edit, reusing the sentences in `prev` outside the
+/// edited paragraphs. See [`Language::resegment`].
+pub fn resegment(
+    lang_code: &str,
+    text: impl AsRef<str>,
+    edit: Range<usize>,
+    prev: &[Sentence],
+) -> Result<Vec<Sentence>> {
+    let language = find_language(lang_code)?;
+    Ok(language.resegment(text.as_ref(), edit, prev))
+}
+
+/// Returns the byte range of each sentence in `bytes`, in source order, without copying
+/// them into a `String` (e.g. for memory-mapped files). `bytes` must be valid UTF-8.
+pub fn segment_bytes_spans(lang_code: &str, bytes: &[u8]) -> Result<Vec<(usize, usize)>> {
+    let language = find_language(lang_code)?;
+    let text = std::str::from_utf8(bytes)?;
+    Ok(language.segment_spans(text))
+}
+
+/// Calls `f` with each sentence of `text` as a slice of it, without allocating them.
+pub fn for_each_sentence<'a>(
+    lang_code: &str,
+    text: &'a str,
+    mut f: impl FnMut(&'a str),
+) -> Result<()> {
+    let language = find_language(lang_code)?;
+    language.for_each_sentence(text, &mut f);
+    Ok(())
+}
+
+/// Segments `text` into paragraphs and their sentences, with their locations.
+pub fn segment_document(lang_code: &str, text: impl AsRef<str>) -> Result<Document> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_document(text.as_ref()))
+}
+
+/// Segments `text`, returning at most its first `n` sentences. Paragraph separators
+/// between them are kept but not counted.
+pub fn segment_limit(lang_code: &str, text: impl AsRef<str>, n: usize) -> Result<Vec<String>> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_limit(text.as_ref(), n))
+}
+
+/// Segments each field of a delimited line, such as a row of a TSV export, on its own.
+/// Quoting is not interpreted, so a delimiter inside a quoted field splits it.
+pub fn segment_delimited(lang_code: &str, line: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    segment_delimited_with_options(lang_code, line, delimiter, &SegmenterOptions::default())
+}
+
+/// Like [`segment_delimited`], applying `options` to the sentences of every field.
+pub fn segment_delimited_with_options(
+    lang_code: &str,
+    line: &str,
+    delimiter: char,
+    options: &SegmenterOptions,
+) -> Result<Vec<Vec<String>>> {
+    let language = find_language(lang_code)?;
+    Ok(line
+        .split(delimiter)
+        .map(|field| language.segment_with_options(field, options))
+        .collect())
+}
+
+/// Returns `true` if `text` holds more than one sentence, stopping at the first boundary.
+pub fn has_boundary(lang_code: &str, text: impl AsRef<str>) -> Result<bool> {
+    let language = find_language(lang_code)?;
+    Ok(language.has_boundary(text.as_ref()))
+}
+
+/// Segments `text`, also returning counts of the boundary decisions taken.
+pub fn segment_with_stats(
+    lang_code: &str,
+    text: impl AsRef<str>,
+) -> Result<(Vec<String>, SegmentStats)> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_with_stats(text.as_ref()))
+}
+
+/// Segments `text`, dropping any candidate boundary for which `filter` returns `false`.
+pub fn segment_with_filter(
+    lang_code: &str,
+    text: impl AsRef<str>,
+    filter: impl Fn(&BoundaryContext) -> bool,
+) -> Result<Vec<String>> {
+    let language = find_language(lang_code)?;
+    Ok(language.segment_with_filter(text.as_ref(), &filter))
+}
+
+/// Segments `text`, also returning the code of the language that handled it
+/// after applying fallbacks.
+pub fn segment_reporting(
+    lang_code: &str,
+    text: impl AsRef<str>,
+) -> Result<(Vec<String>, &'static str)> {
+    let language = find_language(lang_code)?;
+    Ok((language.segment(text.as_ref()), language.language_code()))
+}
+
+/// Returns the code of the language used for `lang_code` after applying fallbacks.
+pub fn resolve_language(lang_code: &str) -> Option<&'static str> {
+    get_language(lang_code).map(|language| language.language_code())
+}
+
+/// Returns the characters that can end a sentence in `lang_code`, in code point order.
+pub fn effective_terminators(lang_code: &str) -> Result<Vec<char>> {
+    let language = find_language(lang_code)?;
+    Ok(language.effective_terminators())
+}
+
+/// Checks that `json` follows the schema of the bundled language data: an object
+/// mapping language codes to their `abbreviation_char`, `abbreviations` and
+/// `exclamation_words`. Returns a message for every problem found.
+pub fn validate_language_data(json: &str) -> std::result::Result<(), Vec<String>> {
+    let data: serde_json::Value =
+        serde_json::from_str(json).map_err(|err| vec![format!("invalid JSON: {}", err)])?;
+    let languages = data.as_object().ok_or_else(|| {
+        vec!["expected an object mapping language codes to their data".to_string()]
+    })?;
+    let mut problems = Vec::new();
+    for (lang_code, entry) in languages {
+        let entry = match entry.as_object() {
+            Some(entry) => entry,
+            None => {
+                problems.push(format!("`{}`: expected an object", lang_code));
+                continue;
+            }
+        };
+        match entry.get("abbreviation_char") {
+            None => problems.push(format!("`{}.abbreviation_char`: missing", lang_code)),
+            Some(serde_json::Value::String(c)) if c.graphemes(true).count() == 1 => {}
+            Some(serde_json::Value::String(c)) => problems.push(format!(
+                "`{}.abbreviation_char`: expected a single grapheme, found `{}`",
+                lang_code, c
+            )),
+            Some(_) => problems.push(format!(
+                "`{}.abbreviation_char`: expected a string",
+                lang_code
+            )),
+        }
+        for field in ["abbreviations", "exclamation_words"] {
+            match entry.get(field) {
+                None => problems.push(format!("`{}.{}`: missing", lang_code, field)),
+                Some(serde_json::Value::Array(words)) => {
+                    for (i, word) in words.iter().enumerate() {
+                        match word.as_str() {
+                            Some(word) if !word.trim().is_empty() => {}
+                            Some(_) => problems
+                                .push(format!("`{}.{}[{}]`: empty word", lang_code, field, i)),
+                            None => problems.push(format!(
+                                "`{}.{}[{}]`: expected a string",
+                                lang_code, field, i
+                            )),
+                        }
+                    }
+                }
+                Some(_) => problems.push(format!(
+                    "`{}.{}`: expected an array of strings",
+                    lang_code, field
+                )),
             }
         }
     }
-    ret_lang
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Returns `true` if `word` is in the abbreviation list of `lang_code`, using the
+/// same casing normalization as the segmenter. A trailing abbreviation mark is optional.
+pub fn is_known_abbreviation(lang_code: &str, word: &str) -> bool {
+    get_language(lang_code).is_some_and(|language| {
+        let word = word
+            .strip_suffix(language.abbreviation_char())
+            .unwrap_or(word);
+        !word.is_empty() && language.matches_abbreviation(word)
+    })
+}
+
+/// Builds a regex matching text enclosed by any of the given `(left, right)` quote pairs.
+pub(crate) fn build_quote_pairs_regex<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Regex {
+    let quotes_regx_str = pairs
+        .into_iter()
+        .map(|(left, right)| format!(r"{}(\n|.)*?{}", left, right))
+        .collect::<Vec<String>>()
+        .join("|");
+    Regex::new(&quotes_regx_str).unwrap()
+}
+
+pub(crate) fn find_language(
+    lang_code: &str,
+) -> Result<&'static (dyn Language + Send + Sync + 'static)> {
+    match get_language(lang_code) {
+        Some(language) => Ok(language),
+        None => bail!("Language `{}` not supported", lang_code),
+    }
+}
+
+fn get_language(lang_code: &str) -> Option<&'static (dyn Language + Send + Sync + 'static)> {
+    resolve_fallbacks(
+        lang_code,
+        &LANGUAGE_REGISTRY,
+        &LANGUAGE_FALLBACKS,
+        &mut HashSet::new(),
+    )
+}
+
+/// Looks `lang_code` up in `registry`, trying its `fallbacks` in order (English if it
+/// has none). Every code is tried at most once, so a cyclic fallback chain ends with `None`.
+fn resolve_fallbacks<'a, T: Copy>(
+    lang_code: &'a str,
+    registry: &HashMap<&str, T>,
+    fallbacks: &HashMap<&str, Vec<&'a str>>,
+    visited: &mut HashSet<&'a str>,
+) -> Option<T> {
+    if !visited.insert(lang_code) {
+        return None;
+    }
+    if let Some(language) = registry.get(lang_code) {
+        return Some(*language);
+    }
+    fallbacks
+        .get(lang_code)
+        .map_or(&["en"][..], |codes| codes.as_slice())
+        .iter()
+        .find_map(|fallback| resolve_fallbacks(fallback, registry, fallbacks, visited))
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -77,31 +426,542 @@ struct LanguageData {
     abbreviation_char: &'static str,
     abbreviations: HashSet<&'static str>,
     exclamation_words: HashSet<&'static str>,
+    #[serde(skip)]
+    max_abbreviation_chars: usize,
+}
+
+/// Adds the ranges of `@mentions` and `#hashtags`, dropping ranges (e.g. emails)
+/// that would cut through one of them.
+fn add_social_token_ranges(text: &str, ranges: &mut Vec<(usize, usize)>) {
+    let tokens: Vec<(usize, usize)> = SOCIAL_TOKEN_REGEX
+        .captures_iter(text)
+        .filter_map(|caps| caps.get(1))
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    ranges.retain(|(start, end)| {
+        !tokens.iter().any(|(token_start, token_end)| {
+            let starts_inside = start > token_start && start < token_end;
+            let ends_inside = end > token_start && end < token_end;
+            starts_inside || ends_inside
+        })
+    });
+    ranges.extend(tokens);
+}
+
+fn accepts_boundary(text: &str, offset: usize, filter: &dyn Fn(&BoundaryContext) -> bool) -> bool {
+    filter(&BoundaryContext {
+        head: &text[..offset],
+        tail: &text[offset..],
+        offset,
+    })
+}
+
+/// Calls `f` with the range between two boundaries of `paragraph`, trimmed as
+/// `trim` says, unless nothing else is left.
+fn emit_span(
+    paragraph: &str,
+    paragraph_offset: usize,
+    from: usize,
+    to: usize,
+    trim: TrimMode,
+    f: &mut dyn FnMut(usize, usize) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let (start, end) = trim.trim(&paragraph[from..to]);
+    let (start, end) = (from + start, from + end);
+    if start < end {
+        f(paragraph_offset + start, paragraph_offset + end)
+    } else {
+        ControlFlow::Continue(())
+    }
+}
+
+/// The characters rules between sections are drawn with, as in `***` or `- - -`
+const RULE_CHARACTERS: &str = "-*=_~#•";
+
+/// Extends `paragraph_breaks` to also match divider lines: three or more rule
+/// characters, possibly spaced out, on a line of their own.
+fn divider_or(paragraph_breaks: &Regex) -> Regex {
+    let rule_character = format!("[{}]", regex::escape(RULE_CHARACTERS));
+    Regex::new(&format!(
+        r"(?:\A|\n+)[^\S\n]*{rule}(?:[^\S\n]*{rule}){{2,}}[^\S\n]*(?:\n+|\z)|{breaks}",
+        rule = rule_character,
+        breaks = paragraph_breaks.as_str()
+    ))
+    .unwrap()
+}
+
+/// Whether `piece` of a text split by `divider_or` is a divider, along with the line
+/// breaks around it.
+fn is_divider(piece: &str) -> bool {
+    piece
+        .chars()
+        .all(|c| c.is_whitespace() || RULE_CHARACTERS.contains(c))
+        && piece
+            .chars()
+            .filter(|c| RULE_CHARACTERS.contains(*c))
+            .count()
+            >= 3
+}
+
+/// Applies `min_graphemes` and `max_graphemes` to the sentences at `spans`.
+fn fit_span_lengths(
+    text: &str,
+    spans: Vec<(usize, usize)>,
+    options: &SegmenterOptions,
+) -> Vec<(usize, usize)> {
+    let spans = match options.min_graphemes {
+        Some(min_graphemes) => merge_short_spans(text, spans, min_graphemes),
+        None => spans,
+    };
+    match options.max_graphemes {
+        Some(max_graphemes) => spans
+            .into_iter()
+            .flat_map(|span| split_long_span(text, span, max_graphemes))
+            .collect(),
+        None => spans,
+    }
+}
+
+/// Merges each sentence shorter than `min_graphemes` into the one after it, or into
+/// the one before it at the end of a paragraph. Paragraph separators are kept.
+fn merge_short_spans(
+    text: &str,
+    spans: Vec<(usize, usize)>,
+    min_graphemes: usize,
+) -> Vec<(usize, usize)> {
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+    // A short sentence, possibly merged with the ones after it, still too short
+    let mut pending: Option<(usize, usize)> = None;
+    let flush = |merged: &mut Vec<(usize, usize)>, pending: (usize, usize)| match merged.last_mut()
+    {
+        Some(last) if !text[last.0..last.1].trim().is_empty() => last.1 = pending.1,
+        _ => merged.push(pending),
+    };
+    for (start, end) in spans {
+        if text[start..end].trim().is_empty() {
+            if let Some(pending) = pending.take() {
+                flush(&mut merged, pending);
+            }
+            merged.push((start, end));
+            continue;
+        }
+        let start = pending
+            .take()
+            .map_or(start, |(pending_start, _)| pending_start);
+        if text[start..end].graphemes(true).count() < min_graphemes {
+            pending = Some((start, end));
+        } else {
+            merged.push((start, end));
+        }
+    }
+    if let Some(pending) = pending {
+        flush(&mut merged, pending);
+    }
+    merged
+}
+
+/// Splits a sentence longer than `max_graphemes` at whitespace, so that each piece
+/// fits unless it is a single longer word.
+fn split_long_span(text: &str, span: (usize, usize), max_graphemes: usize) -> Vec<(usize, usize)> {
+    let (start, end) = span;
+    let mut pieces = Vec::new();
+    let mut piece_start = start;
+    let mut n_graphemes = 0;
+    // The latest run of whitespace in the current piece, where it can be split
+    let mut last_space: Option<(usize, usize)> = None;
+    for (offset, grapheme) in text[start..end].grapheme_indices(true) {
+        let pos = start + offset;
+        if grapheme.trim().is_empty() {
+            last_space = match last_space {
+                Some((space_start, space_end)) if space_end == pos => {
+                    Some((space_start, pos + grapheme.len()))
+                }
+                _ => Some((pos, pos + grapheme.len())),
+            };
+        }
+        n_graphemes += 1;
+        if n_graphemes > max_graphemes {
+            if let Some((space_start, space_end)) = last_space.take() {
+                if space_start > piece_start {
+                    pieces.push((piece_start, space_start));
+                }
+                piece_start = space_end;
+                n_graphemes = text[piece_start..pos + grapheme.len()]
+                    .graphemes(true)
+                    .count();
+            }
+        }
+    }
+    if piece_start < end {
+        pieces.push((piece_start, end));
+    }
+    pieces
+}
+
+/// Returns the first word of `tail`, skipping leading whitespace and opening punctuation.
+pub(crate) fn lookahead_word(tail: &str) -> Option<&str> {
+    let tail = tail.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let end = tail
+        .find(|c: char| !c.is_alphanumeric())
+        .unwrap_or(tail.len());
+    (end > 0).then(|| &tail[..end])
 }
 
-pub struct GraphemeCursor {
+/// Pairs straight double quotes, which open and close with the same character.
+/// A quote at the start, or after whitespace, an opening bracket or a dash, opens
+/// a quoted span when text follows it; any other closes the innermost open one.
+/// This way `"She said "hello." Then left."` nests instead of pairing left to right.
+fn straight_quote_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut open_quotes = Vec::new();
+    let mut previous = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((pos, c)) = chars.next() {
+        if c == '"' {
+            let next = chars.peek().map(|(_, next)| *next);
+            let opens = previous
+                .is_none_or(|p: char| p.is_whitespace() || "([{<«‹“‘—–-".contains(p))
+                && next.is_some_and(|n: char| {
+                    !n.is_whitespace() && (!n.is_ascii_punctuation() || "([{'".contains(n))
+                });
+            match open_quotes.pop() {
+                Some(start) if !opens => ranges.push((start, pos + 1)),
+                // A quote with nothing to close opens one, as in `said"hi"`
+                Some(start) => open_quotes.extend([start, pos]),
+                None => open_quotes.push(pos),
+            }
+        }
+        previous = Some(c);
+    }
+    ranges
+}
+
+/// Where a quote closed at `end` really ends, skipping over apostrophes inside
+/// words such as `don't` or `don’t`, which share the closing quote character.
+fn closing_quote_end(text: &str, mut end: usize) -> Option<usize> {
+    loop {
+        let closing_char = text[..end].chars().next_back()?;
+        if !(closing_char == '\'' || WORD_INTERNAL_QUOTES.contains(&closing_char)) {
+            return Some(end);
+        }
+        let start = end - closing_char.len_utf8();
+        let inside_word = text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric())
+            && text[end..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric());
+        if !inside_word {
+            return Some(end);
+        }
+        end += text[end..].find(closing_char)? + closing_char.len_utf8();
+    }
+}
+
+/// Returns `true` if the quote character at `pos` directly follows a letter,
+/// in which case it is part of the word (e.g. `Hawai‘i`) and doesn't open a quotation.
+fn opens_inside_word(text: &str, pos: usize) -> bool {
+    let opening_char = match text[pos..].chars().next() {
+        Some(c) => c,
+        None => return false,
+    };
+    if !WORD_INTERNAL_QUOTES.contains(&opening_char) {
+        return false;
+    }
+    text[..pos]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphabetic())
+}
+
+/// Returns the offset of the first bracket or quote in `text` that `language` finds
+/// no closing for, so that text added later could still close it.
+pub(crate) fn first_unclosed_opener(language: &dyn Language, text: &str) -> Option<usize> {
+    let ranges = language.get_skippable_ranges(text);
+    let quote_openers = language
+        .quote_pairs()
+        .iter()
+        .flat_map(|(left, _)| text.match_indices(left))
+        .map(|(pos, _)| pos)
+        .filter(|pos| !opens_inside_word(text, *pos));
+    let other_openers = text
+        .match_indices(['(', '（', '<', '{', '[', '"'])
+        .map(|(pos, _)| pos);
+    quote_openers
+        .chain(other_openers)
+        .filter(|pos| {
+            !ranges
+                .iter()
+                .any(|(start, end)| (*start..*end).contains(pos))
+        })
+        .min()
+}
+
+/// A sentence along with its location in the segmented text.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Sentence {
+    pub text: String,
+    /// Byte offset of the start of the sentence.
+    pub start: usize,
+    /// Byte offset of the end of the sentence.
+    pub end: usize,
+    /// The terminator that closed the sentence, `None` if it is unterminated.
+    pub terminator: Option<char>,
+    /// Position of the sentence among the ones returned with it, counting from 0, so the
+    /// indices of a result are contiguous. Paragraph separators count wherever they are
+    /// returned as sentences, as by `segment_detailed`; a `Document` leaves them out and
+    /// numbers the sentences of all its paragraphs in one sequence. Sentences are
+    /// always returned in source order, so sorting by it restores them.
+    pub index: usize,
+    /// The script most of its letters are written in, `None` if it has no letters.
+    pub script: Option<Script>,
+}
+
+/// Numbers `sentences` in order.
+fn with_indices(mut sentences: Vec<Sentence>) -> Vec<Sentence> {
+    for (index, sentence) in sentences.iter_mut().enumerate() {
+        sentence.index = index;
+    }
+    sentences
+}
+
+/// A text split into paragraphs, each split into sentences.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Document {
+    pub paragraphs: Vec<Paragraph>,
+}
+
+/// A paragraph along with its sentences.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Paragraph {
+    /// Byte offsets of the start and end of the paragraph, excluding the
+    /// line breaks separating it from its neighbours.
+    pub span: (usize, usize),
+    pub sentences: Vec<Sentence>,
+}
+
+/// Counts of the decisions taken while segmenting a text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SegmentStats {
+    /// Runs of sentence terminators looked at.
+    pub terminators_examined: usize,
+    /// Sentence boundaries accepted after a terminator.
+    pub boundaries_accepted: usize,
+    /// Terminators ending an abbreviation or an initial.
+    pub abbreviation_suppressions: usize,
+    /// Terminators inside quotes, parentheses, or another protected range.
+    pub quote_suppressions: usize,
+    /// Terminators followed by numbered references such as `[1]`.
+    pub numbered_references: usize,
+}
+
+/// A sentence boundary about to be accepted by the segmenter.
+pub struct BoundaryContext<'a> {
+    /// The input text before the boundary.
+    pub head: &'a str,
+    /// The input text after the boundary.
+    pub tail: &'a str,
+    /// The byte offset of the boundary in the input text.
+    pub offset: usize,
+}
+
+/// The state of a search for sentence boundaries, handed to [`Language::find_boundary`]
+/// along with each terminator.
+#[non_exhaustive]
+pub struct BoundarySearch<'s> {
+    /// The options the text is segmented with.
+    pub options: &'s SegmenterOptions,
+    /// Counts of the decisions taken so far.
+    pub stats: &'s mut SegmentStats,
+}
+
+/// The graphemes of a paragraph, by byte offset.
+pub struct GraphemeCursor<'t> {
+    text: &'t str,
+    /// `None` for paragraphs whose graphemes are all single bytes, so that offsets are
+    /// found by arithmetic
+    index: Option<GraphemeIndex<'t>>,
+}
+
+struct GraphemeIndex<'t> {
+    grapheme_indices: HashMap<usize, &'t str>,
     grapheme_offsets: Vec<usize>,
 }
 
-impl GraphemeCursor {
+impl<'t> GraphemeCursor<'t> {
+    fn new(text: &'t str) -> Self {
+        // Every ASCII character is a grapheme of its own, apart from `\r\n`
+        if text.is_ascii() && !text.contains('\r') {
+            return Self { text, index: None };
+        }
+        Self::indexed(text)
+    }
+    /// Indexes the graphemes of `text` whatever its characters.
+    fn indexed(text: &'t str) -> Self {
+        let (grapheme_indices, grapheme_offsets) = index_graphemes(text);
+        Self {
+            text,
+            index: Some(GraphemeIndex {
+                grapheme_indices,
+                grapheme_offsets,
+            }),
+        }
+    }
+    /// Returns the grapheme starting at `pos`, which must start one.
+    fn grapheme_at(&self, pos: usize) -> &'t str {
+        match self.index {
+            Some(ref index) => index.grapheme_indices[&pos],
+            None => &self.text[pos..pos + 1],
+        }
+    }
     fn next_grapheme(&self, pos: usize) -> Option<usize> {
-        self.grapheme_offsets.iter().find(|p| **p > pos).copied()
+        match self.index {
+            Some(ref index) => {
+                let idx = index.grapheme_offsets.partition_point(|p| *p <= pos);
+                index.grapheme_offsets.get(idx).copied()
+            }
+            None => Some(pos + 1).filter(|next| *next < self.text.len()),
+        }
     }
     #[allow(dead_code)]
     fn prev_grapheme(&self, pos: usize) -> Option<usize> {
-        self.grapheme_offsets
+        match self.index {
+            Some(ref index) => index
+                .grapheme_offsets
+                .iter()
+                .rev()
+                .find(|p| **p < pos)
+                .copied(),
+            None => pos.min(self.text.len()).checked_sub(1),
+        }
+    }
+}
+
+/// The ranges of a paragraph that no boundary may fall inside, such as quotes and
+/// brackets, sorted so that each lookup is a binary search.
+struct RangeIndex {
+    /// Sorted by start.
+    by_start: Vec<(usize, usize)>,
+    /// The furthest end among `by_start[..=i]`, for ranges nested in one another.
+    max_ends: Vec<usize>,
+    /// `(end, start)` pairs, sorted by end.
+    by_end: Vec<(usize, usize)>,
+}
+
+impl RangeIndex {
+    /// Indexes `ranges`, keeping only the first `max_ranges` of them in text order.
+    fn new(mut ranges: Vec<(usize, usize)>, max_ranges: Option<usize>) -> Self {
+        ranges.sort_unstable();
+        if let Some(max_ranges) = max_ranges {
+            ranges.truncate(max_ranges);
+        }
+        let max_ends = ranges
+            .iter()
+            .scan(0, |max_end, (_, end)| {
+                *max_end = (*max_end).max(*end);
+                Some(*max_end)
+            })
+            .collect();
+        let mut by_end: Vec<(usize, usize)> =
+            ranges.iter().map(|(start, end)| (*end, *start)).collect();
+        by_end.sort_unstable();
+        Self {
+            by_start: ranges,
+            max_ends,
+            by_end,
+        }
+    }
+
+    /// Whether `pos` is strictly inside a range.
+    fn contains(&self, pos: usize) -> bool {
+        let n_before = self.by_start.partition_point(|(start, _)| *start < pos);
+        n_before > 0 && self.max_ends[n_before - 1] > pos
+    }
+
+    /// Whether a range containing `pos` ends at `end`.
+    fn ends_right_after(&self, pos: usize, end: usize) -> bool {
+        let first = self
+            .by_end
+            .partition_point(|(range_end, _)| *range_end < end);
+        self.by_end[first..]
             .iter()
-            .rev()
-            .find(|p| **p < pos)
-            .copied()
+            .take_while(|(range_end, _)| *range_end == end)
+            .any(|(_, start)| *start < pos)
+    }
+
+    /// Whether a range starts at `pos`.
+    fn starts_at(&self, pos: usize) -> bool {
+        let first = self.by_start.partition_point(|(start, _)| *start < pos);
+        self.by_start
+            .get(first)
+            .is_some_and(|(start, _)| *start == pos)
+    }
+}
+
+/// Whether `c` separates words, as matched by `WORD_SPLIT_REGEX`.
+fn is_word_separator(c: char) -> bool {
+    c.is_whitespace() || c == '.'
+}
+
+/// Returns the last piece of `text` split by `WORD_SPLIT_REGEX`, looking only at its
+/// end, as the text before a terminator can be the whole paragraph.
+pub(crate) fn last_word(text: &str) -> &str {
+    text.rsplit(is_word_separator).next().unwrap_or(text)
+}
+
+/// Maps the offset of each grapheme in `text` to the grapheme, and lists the offsets
+/// in order.
+fn index_graphemes(text: &str) -> (HashMap<usize, &str>, Vec<usize>) {
+    let grapheme_indices: HashMap<usize, &str> = text.grapheme_indices(false).collect();
+    let mut grapheme_offsets: Vec<usize> = grapheme_indices.keys().copied().collect();
+    grapheme_offsets.sort_unstable();
+    (grapheme_indices, grapheme_offsets)
+}
+
+/// Duplicates a language behind a trait object. Implemented for every `Clone` language.
+pub trait LanguageClone {
+    fn clone_box(&self) -> Box<dyn CloneableLanguage + Send + Sync>;
+}
+
+impl<T: Language + Clone + Send + Sync + 'static> LanguageClone for T {
+    fn clone_box(&self) -> Box<dyn CloneableLanguage + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+/// A language that can be cloned behind a trait object, e.g. to copy a registry of
+/// `Box<dyn CloneableLanguage + Send + Sync>`.
+pub trait CloneableLanguage: Language + LanguageClone {}
+
+impl<T: Language + LanguageClone + ?Sized> CloneableLanguage for T {}
+
+impl Clone for Box<dyn CloneableLanguage + Send + Sync> {
+    fn clone(&self) -> Self {
+        self.clone_box()
     }
 }
 
 pub trait Language {
     fn language_code(&self) -> &'static str;
 
+    /// The opening and closing quotes whose contents are kept within one sentence.
+    fn quote_pairs(&self) -> &'static [(&'static str, &'static str)] {
+        &QUOTE_PAIRS_ARRAY
+    }
+    /// Matches the spans enclosed by `quote_pairs`. It is built the first time a given
+    /// set of pairs is seen and shared by every language using it.
     fn quote_pairs_regex(&self) -> &'static Regex {
-        &QUOTE_PAIRS_REGEX
+        let pairs = self.quote_pairs();
+        if let Some(regex) = QUOTE_PAIRS_REGEXES.read().unwrap().get(pairs) {
+            return regex;
+        }
+        QUOTE_PAIRS_REGEXES
+            .write()
+            .unwrap()
+            .entry(pairs)
+            .or_insert_with(|| Box::leak(Box::new(build_quote_pairs_regex(pairs.iter().copied()))))
     }
     fn numbered_reference_regex(&self) -> &Regex {
         &NUMBERED_REFERENCE_REGEX
@@ -109,128 +969,693 @@ pub trait Language {
     fn sentence_break_regex(&self) -> &Regex {
         &GLOBAL_SENTENCE_BOUNDARY_REGEX
     }
+    /// Weak separators, such as the Greek ano teleia, that never end a sentence in this
+    /// language, even when `sentence_break_regex` or the options would match them.
+    fn never_break_characters(&self) -> &'static [char] {
+        &[]
+    }
     fn get_lastword<'a>(&'a self, text: &'a str) -> Option<&'a str> {
-        WORD_SPLIT_REGEX.split(text).last()
+        Some(last_word(text))
     }
 
     fn find_boundary<'a>(
         &self,
         text: &'a str,
-        grapheme_indices: &HashMap<usize, &str>,
         cursor: &GraphemeCursor,
         mtch: Match<'a>,
+        search: &mut BoundarySearch,
     ) -> Option<(usize, bool)> {
+        let options = search.options;
         let (match_start, match_end) = (mtch.start(), mtch.end());
         let next_char_offset = cursor.next_grapheme(match_start)?;
-        let tail = &text[next_char_offset..];
+        let mut tail = &text[next_char_offset..];
         let head = &text[..match_start];
+        let mut boundary = match_end;
+
+        // Nothing before it to end, as in `.NET` or a stray leading period
+        if head
+            .chars()
+            .all(|c| c.is_whitespace() || GLOBAL_SENTENCE_TERMINATORS.contains(&c))
+        {
+            return None;
+        }
+
+        let never_break_characters = self.never_break_characters();
+        if mtch
+            .as_str()
+            .chars()
+            .all(|c| never_break_characters.contains(&c))
+        {
+            return None;
+        }
+
+        if options.ends_list_marker(head, mtch.as_str(), tail)
+            || options.ends_legal_reference(head, mtch.as_str(), tail)
+        {
+            return None;
+        }
 
         let number_ref_match = self.numbered_reference_regex().find(tail);
 
         if let Some(number_ref_match) = number_ref_match {
             let ref_num_end = mtch.end() + number_ref_match.end();
             let ref_num_end = cursor.next_grapheme(ref_num_end).unwrap_or(ref_num_end);
-            return Some((ref_num_end, true));
+            search.stats.numbered_references += 1;
+            if !options.attach_numbered_references {
+                return Some((ref_num_end, true));
+            }
+            tail = &tail[number_ref_match.end()..];
+            boundary = match_end + number_ref_match.end();
         }
 
-        if self.continue_in_next_word(tail) {
+        if self.continue_in_next_word(tail)
+            && !options.starts_with_capitalized_word(tail)
+            && !options.starts_with_number(tail)
+            && !options.starts_list_item(tail)
+        {
             return None;
         }
 
-        if self.is_abbreviation(head, tail, grapheme_indices[&match_start]) {
+        let separator = cursor.grapheme_at(match_start);
+        if self.is_abbreviation(head, tail, separator) || self.is_initial(head, tail, separator) {
+            search.stats.abbreviation_suppressions += 1;
             return None;
         }
 
-        if self.is_exclamation_word(head, tail) {
+        if self.ends_with_exclamation_word(head, tail, mtch.as_str()) {
             return None;
         }
 
-        Some((match_end, false))
+        // Start the next list item with its marker, leaving the line break before it
+        // to the sentence it ends
+        if options.starts_list_item(&text[boundary..]) {
+            if let Some(line_break) = text[boundary..].find('\n') {
+                boundary += line_break + 1;
+            }
+        }
+
+        Some((boundary, false))
     }
 
     fn continue_in_next_word(&self, text_after_boundary: &str) -> bool {
         text_after_boundary
             .chars()
             .next()
-            .map_or(false, |c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            || WORD_CONTINUATION_MARK_REGEX.is_match(text_after_boundary)
     }
 
     fn get_skippable_ranges(&self, text: &str) -> Vec<(usize, usize)> {
-        let mut bounds = Vec::from_iter(
-            self.quote_pairs_regex()
-                .find_iter(text)
-                .chain(EMAIL_REGEX.find_iter(text))
-                .map(|m| (m.start(), m.end())),
-        );
-        for m in PARENS_REGEX.find_iter(text).flatten() {
+        let mut bounds = Vec::new();
+        let quote_pairs_regex = self.quote_pairs_regex();
+        let mut search_start = 0;
+        while let Some(m) = quote_pairs_regex.find_at(text, search_start) {
+            if opens_inside_word(text, m.start()) {
+                // An apostrophe-like letter (ʻokina, elision) rather than an opening quote
+                search_start = m.start() + text[m.start()..].chars().next().unwrap().len_utf8();
+                continue;
+            }
+            match closing_quote_end(text, m.end()) {
+                Some(end) => {
+                    bounds.push((m.start(), end));
+                    search_start = end;
+                }
+                // Only apostrophes follow, as in `Rock 'n roll, don't`
+                None => {
+                    search_start = m.start() + text[m.start()..].chars().next().unwrap().len_utf8()
+                }
+            }
+        }
+        bounds.extend(straight_quote_ranges(text));
+        bounds.extend(EMAIL_REGEX.find_iter(text).map(|m| (m.start(), m.end())));
+        // The backtracking limit can be hit on very long paragraphs, which would keep
+        // yielding the same error
+        for m in PARENS_REGEX.find_iter(text).map_while(Result::ok) {
             bounds.push((m.start(), m.end()))
         }
         bounds
     }
 
     fn segment(&self, text: &str) -> Vec<String> {
-        let mut sentences = Vec::new();
-
-        for paragraph in CONSECUTIVE_NEWLINES_REGEX.split_inclusive(text) {
-            let grapheme_indices: HashMap<usize, &str> =
-                paragraph.grapheme_indices(false).collect();
-            let mut grapheme_offsets: Vec<usize> = grapheme_indices.keys().copied().collect();
-            grapheme_offsets.sort_unstable();
-            let cursor = GraphemeCursor { grapheme_offsets };
-
-            let mut boundaries = vec![0];
-            let skippable_ranges = self.get_skippable_ranges(paragraph);
-
-            for mtch in self.sentence_break_regex().find_iter(paragraph) {
-                if let Some((mut boundary, is_num_ref)) =
-                    self.find_boundary(paragraph, &grapheme_indices, &cursor, mtch)
-                {
-                    let mut in_range = false;
-                    if is_num_ref {
-                        boundaries.push(boundary);
-                        continue;
-                    }
-                    'skip_ranges: for (qstart, qend) in skippable_ranges.iter() {
-                        let next_grapheme = cursor.next_grapheme(boundary).unwrap_or(boundary);
-                        if (boundary > *qstart) && (boundary < *qend) {
-                            if (next_grapheme == *qend) && self.is_punctuation_between_quotes() {
-                                boundary = *qend;
-                                in_range = false;
-                            } else {
-                                in_range = true;
-                            }
-                            break 'skip_ranges;
-                        }
-                    }
-                    if in_range {
-                        continue;
-                    }
-
-                    boundaries.push(boundary);
-                }
-            }
+        self.segment_with_options(text, &SegmenterOptions::default())
+    }
 
-            for (i, j) in boundaries.iter().zip(
-                boundaries
-                    .iter()
-                    .skip(1)
-                    .chain(std::iter::once(&paragraph.len())),
-            ) {
-                let sentence = &paragraph[*i..*j];
-                if !sentence.is_empty() {
-                    sentences.push(sentence.trim_matches(' ').to_string());
+    fn segment_with_options(&self, text: &str, options: &SegmenterOptions) -> Vec<String> {
+        let spans = self.segment_spans_with_filter(text, options, &|_| true);
+        let mut sentences: Vec<String> = fit_span_lengths(text, spans, options)
+            .into_iter()
+            .map(|(start, end)| {
+                let sentence = &text[start..end];
+                // Paragraph separators are kept as they are
+                if sentence.trim().is_empty() {
+                    return sentence.to_string();
                 }
-            }
+                let sentence = if options.strip_references {
+                    ANY_NUMBERED_REFERENCE_REGEX.replace_all(sentence, "")
+                } else {
+                    Cow::Borrowed(sentence)
+                };
+                let sentence = if options.collapse_internal_whitespace {
+                    sentence.split_whitespace().collect::<Vec<&str>>().join(" ")
+                } else {
+                    sentence.into_owned()
+                };
+                self.postprocess(sentence)
+            })
+            .collect();
+        if options.dedup_adjacent {
+            sentences.dedup_by(|sentence, previous| sentence.trim() == previous.trim());
         }
-
         sentences
     }
 
-    fn is_punctuation_between_quotes(&self) -> bool {
-        false
+    /// A final transform applied to each sentence returned by `segment`.
+    fn postprocess(&self, sentence: String) -> String {
+        sentence
     }
-    fn is_abbreviation(&self, head: &str, _tail: &str, separator: &str) -> bool {
-        if self.abbreviation_char() != separator {
+
+    fn segment_with_filter(
+        &self,
+        text: &str,
+        filter: &dyn Fn(&BoundaryContext) -> bool,
+    ) -> Vec<String> {
+        self.segment_spans_with_filter(text, &SegmenterOptions::default(), filter)
+            .into_iter()
+            .map(|(start, end)| text[start..end].to_string())
+            .collect()
+    }
+
+    fn segment_detailed(&self, text: &str) -> Vec<Sentence> {
+        self.segment_spans(text)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end))| {
+                let sentence = &text[start..end];
+                Sentence {
+                    text: sentence.to_string(),
+                    start,
+                    end,
+                    terminator: self.sentence_terminator(sentence),
+                    index,
+                    script: dominant_script(sentence),
+                }
+            })
+            .collect()
+    }
+
+    /// Segments `text` into paragraphs, leaving out the paragraph separators
+    /// and any blank paragraphs. The sentences are numbered through the whole document.
+    fn segment_document(&self, text: &str) -> Document {
+        let mut sentences = self.segment_detailed(text).into_iter().peekable();
+        let mut paragraphs = Vec::new();
+        let separators = CONSECUTIVE_NEWLINES_REGEX
+            .find_iter(text)
+            .map(|m| (m.start(), m.end()))
+            .chain(std::iter::once((text.len(), text.len())));
+        let mut start = 0;
+        // Separators are left out, so the sentences are numbered again
+        let mut n_sentences = 0;
+        for (separator_start, separator_end) in separators {
+            let span = (start, separator_start);
+            start = separator_end;
+            let mut paragraph_sentences = Vec::new();
+            while let Some(sentence) = sentences.next_if(|sentence| sentence.start < span.1) {
+                if !sentence.text.trim().is_empty() {
+                    paragraph_sentences.push(Sentence {
+                        index: n_sentences,
+                        ..sentence
+                    });
+                    n_sentences += 1;
+                }
+            }
+            if !paragraph_sentences.is_empty() {
+                paragraphs.push(Paragraph {
+                    span,
+                    sentences: paragraph_sentences,
+                });
+            }
+        }
+        Document { paragraphs }
+    }
+
+    /// Re-segments `text` after an edit, given the sentences `prev` of the text before it.
+    /// `edit` is the byte range of `text` holding the new content (empty for a deletion).
+    /// Only the paragraphs touching the edit are segmented again, the sentences of the
+    /// others are reused; if they can't be matched to `text` everything is re-segmented.
+    fn resegment(&self, text: &str, edit: Range<usize>, prev: &[Sentence]) -> Vec<Sentence> {
+        let region_start = CONSECUTIVE_NEWLINES_REGEX
+            .find_iter(text)
+            .take_while(|m| m.end() < edit.start)
+            .last()
+            .map_or(0, |m| m.end());
+        let region_end = CONSECUTIVE_NEWLINES_REGEX
+            .find_iter(text)
+            .find(|m| m.start() > edit.end)
+            .map_or(text.len(), |m| m.start());
+        let region = &text[region_start..region_end];
+        if region.trim().is_empty() {
+            return self.segment_detailed(text);
+        }
+
+        let mut sentences: Vec<Sentence> = prev
+            .iter()
+            .take_while(|sentence| sentence.end <= region_start)
+            .cloned()
+            .collect();
+        sentences.extend(
+            self.segment_detailed(region)
+                .into_iter()
+                .map(|sentence| Sentence {
+                    start: region_start + sentence.start,
+                    end: region_start + sentence.end,
+                    ..sentence
+                }),
+        );
+        if region_end == text.len() {
+            return with_indices(sentences);
+        }
+
+        // The text after the region is unchanged, so its sentences keep their
+        // distance from the end of the text.
+        let last_end = match prev.last() {
+            Some(sentence) => sentence.end,
+            None => return self.segment_detailed(text),
+        };
+        let delta = text.trim_end_matches(SENTENCE_PADDING).len() as isize - last_end as isize;
+        let mut tail = Vec::new();
+        for sentence in prev.iter().rev() {
+            let start = match sentence.start.checked_add_signed(delta) {
+                Some(start) if start >= region_end => start,
+                _ => break,
+            };
+            let end = start + sentence.text.len();
+            if text.get(start..end) != Some(sentence.text.as_str()) {
+                return self.segment_detailed(text);
+            }
+            tail.push(Sentence {
+                start,
+                end,
+                ..sentence.clone()
+            });
+        }
+        if tail.last().map(|sentence| sentence.start) != Some(region_end) {
+            return self.segment_detailed(text);
+        }
+        sentences.extend(tail.into_iter().rev());
+        with_indices(sentences)
+    }
+
+    /// Returns the characters matched by `sentence_break_regex`, in code point order.
+    fn effective_terminators(&self) -> Vec<char> {
+        let sentence_break_regex = self.sentence_break_regex();
+        // Language regexes list their terminators literally, so the candidates are
+        // the global ones and whatever appears in the pattern
+        let mut terminators: Vec<char> = GLOBAL_SENTENCE_TERMINATORS
+            .into_iter()
+            .chain(sentence_break_regex.as_str().chars())
+            .filter(|c| sentence_break_regex.is_match(c.encode_utf8(&mut [0; 4])))
+            .filter(|c| !self.never_break_characters().contains(c))
+            .collect();
+        terminators.sort_unstable();
+        terminators.dedup();
+        terminators
+    }
+
+    /// Returns the terminator ending `sentence`, looking past closing quotes,
+    /// brackets, and numbered references.
+    fn sentence_terminator(&self, sentence: &str) -> Option<char> {
+        let sentence = sentence.trim_end();
+        let sentence = TRAILING_NUMBERED_REFERENCE_REGEX
+            .find(sentence)
+            .map_or(sentence, |m| &sentence[..m.start()]);
+        let last_char = sentence
+            .trim_end_matches(CLOSING_PUNCTUATION)
+            .chars()
+            .next_back()?;
+        let mut buf = [0; 4];
+        self.sentence_break_regex()
+            .is_match(last_char.encode_utf8(&mut buf))
+            .then_some(last_char)
+    }
+
+    /// Returns the byte offset where each sentence of `text` begins, whitespace
+    /// included, so that slicing `text` at them gives back all of it.
+    fn boundary_offsets(&self, text: &str) -> Vec<usize> {
+        let options = SegmenterOptions {
+            trim: TrimMode::None,
+            ..Default::default()
+        };
+        let mut offsets = Vec::new();
+        self.for_each_span(
+            text,
+            &options,
+            &|_| true,
+            &mut SegmentStats::default(),
+            &mut |start, _| {
+                offsets.push(start);
+                ControlFlow::Continue(())
+            },
+        );
+        offsets
+    }
+
+    /// Returns the byte range of each sentence in `text`, in source order.
+    fn segment_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        self.segment_spans_with_filter(text, &SegmenterOptions::default(), &|_| true)
+    }
+
+    fn segment_spans_with_filter(
+        &self,
+        text: &str,
+        options: &SegmenterOptions,
+        filter: &dyn Fn(&BoundaryContext) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        self.for_each_span(
+            text,
+            options,
+            filter,
+            &mut SegmentStats::default(),
+            &mut |start, end| {
+                spans.push((start, end));
+                ControlFlow::Continue(())
+            },
+        );
+        spans
+    }
+
+    /// Returns at most the first `n` sentences of `text`, without looking any further.
+    /// Paragraph separators between them are kept but not counted.
+    fn segment_limit(&self, text: &str, n: usize) -> Vec<String> {
+        let mut sentences = Vec::new();
+        if n == 0 {
+            return sentences;
+        }
+        let mut n_sentences = 0;
+        self.for_each_span(
+            text,
+            &SegmenterOptions::default(),
+            &|_| true,
+            &mut SegmentStats::default(),
+            &mut |start, end| {
+                let sentence = &text[start..end];
+                if !sentence.trim().is_empty() {
+                    n_sentences += 1;
+                }
+                sentences.push(sentence.to_string());
+                if n_sentences < n {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            },
+        );
+        sentences
+    }
+
+    /// Whether `text` holds more than one sentence, i.e. a boundary that is followed
+    /// by more text. Segmentation stops as soon as the second sentence is found.
+    fn has_boundary(&self, text: &str) -> bool {
+        let mut n_sentences = 0;
+        self.for_each_span(
+            text,
+            &SegmenterOptions::default(),
+            &|_| true,
+            &mut SegmentStats::default(),
+            &mut |_, _| {
+                n_sentences += 1;
+                if n_sentences < 2 {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            },
+        );
+        n_sentences > 1
+    }
+
+    fn segment_with_stats(&self, text: &str) -> (Vec<String>, SegmentStats) {
+        let mut sentences = Vec::new();
+        let mut stats = SegmentStats::default();
+        self.for_each_span(
+            text,
+            &SegmenterOptions::default(),
+            &|_| true,
+            &mut stats,
+            &mut |start, end| {
+                sentences.push(text[start..end].to_string());
+                ControlFlow::Continue(())
+            },
+        );
+        (sentences, stats)
+    }
+
+    /// Calls `f` with each sentence of `text` as it is found, without collecting them.
+    fn for_each_sentence<'a>(&self, text: &'a str, f: &mut dyn FnMut(&'a str)) {
+        self.for_each_span(
+            text,
+            &SegmenterOptions::default(),
+            &|_| true,
+            &mut SegmentStats::default(),
+            &mut |start, end| {
+                f(&text[start..end]);
+                ControlFlow::Continue(())
+            },
+        );
+    }
+
+    /// Calls `f` with the byte range of each sentence in `text`, in source order,
+    /// until it returns `ControlFlow::Break`.
+    fn for_each_span(
+        &self,
+        text: &str,
+        options: &SegmenterOptions,
+        filter: &dyn Fn(&BoundaryContext) -> bool,
+        stats: &mut SegmentStats,
+        f: &mut dyn FnMut(usize, usize) -> ControlFlow<()>,
+    ) {
+        // Paragraph separators alone don't make a sentence
+        if text.trim().is_empty() {
+            return;
+        }
+        // Nor do they before the first sentence or after the last, unless all the text
+        // is kept
+        let (content_start, content_end) =
+            (text.len() - text.trim_start().len(), text.trim_end().len());
+        let keeps_all = options.trim == TrimMode::None;
+        let f = &mut |start, end| {
+            if !keeps_all && (end <= content_start || start >= content_end) {
+                ControlFlow::Continue(())
+            } else {
+                f(start, end)
+            }
+        };
+        let extra_terminators = options.extra_terminators();
+        let extended_break_regex;
+        let sentence_break_regex = if extra_terminators.is_empty() {
+            self.sentence_break_regex()
+        } else {
+            extended_break_regex = Regex::new(&format!(
+                "{}|[{}]+",
+                self.sentence_break_regex().as_str(),
+                regex::escape(&String::from_iter(extra_terminators))
+            ))
+            .unwrap();
+            &extended_break_regex
+        };
+        let punctuation_inside_quotes = options
+            .punctuation_inside_quotes
+            .unwrap_or_else(|| self.is_punctuation_between_quotes());
+        // Whether a colon that doesn't introduce a quotation may still end a sentence,
+        // only needed when colons were added to catch quotations
+        let colon_is_terminator = options.split_on_colon_before_quote == Some(true)
+            && (options.split_on_colon || self.effective_terminators().contains(&':'));
+        let mut paragraph_offset = 0;
+        let mut search = BoundarySearch { options, stats };
+
+        let paragraph_break_regex = match (options.dividers_as_breaks, options.line_is_paragraph) {
+            (true, true) => &DIVIDER_OR_LINE_BREAKS_REGEX,
+            (true, false) => &DIVIDER_OR_CONSECUTIVE_NEWLINES_REGEX,
+            (false, true) => &LINE_BREAKS_REGEX,
+            (false, false) => &CONSECUTIVE_NEWLINES_REGEX,
+        };
+        for paragraph in paragraph_break_regex.split_inclusive(text) {
+            if options.dividers_as_breaks && is_divider(paragraph) {
+                // A divider only ends the sentence before it
+                paragraph_offset += paragraph.len();
+                continue;
+            }
+            // Nothing to decide without terminators, so skip the per-grapheme bookkeeping
+            if !options.newline_is_boundary && !sentence_break_regex.is_match(paragraph) {
+                if emit_span(
+                    paragraph,
+                    paragraph_offset,
+                    0,
+                    paragraph.len(),
+                    options.trim,
+                    f,
+                )
+                .is_break()
+                {
+                    return;
+                }
+                paragraph_offset += paragraph.len();
+                continue;
+            }
+            let cursor = GraphemeCursor::new(paragraph);
+
+            let mut boundaries = vec![0];
+            // Index of the first boundary whose sentence has not been emitted yet
+            let mut emitted = 0;
+            let mut skippable_ranges = self.get_skippable_ranges(paragraph);
+            if options.social_text {
+                add_social_token_ranges(paragraph, &mut skippable_ranges);
+            }
+            if options.protect_technical_tokens {
+                skippable_ranges.extend(
+                    TECHNICAL_TOKEN_REGEX
+                        .find_iter(paragraph)
+                        .map(|m| (m.start(), m.end())),
+                );
+            }
+            if options.latex_math {
+                skippable_ranges.extend(
+                    LATEX_MATH_REGEX
+                        .find_iter(paragraph)
+                        .filter(|m| m.as_str() != "\\$")
+                        .map(|m| (m.start(), m.end())),
+                );
+            }
+            let skippable_ranges = RangeIndex::new(skippable_ranges, options.max_skippable_ranges);
+
+            for mtch in sentence_break_regex.find_iter(paragraph) {
+                search.stats.terminators_examined += 1;
+                if let Some((mut boundary, is_num_ref)) =
+                    self.find_boundary(paragraph, &cursor, mtch, &mut search)
+                {
+                    if is_num_ref {
+                        if accepts_boundary(text, paragraph_offset + boundary, filter) {
+                            search.stats.boundaries_accepted += 1;
+                            boundaries.push(boundary);
+                        }
+                    } else {
+                        let mut in_range = false;
+                        if skippable_ranges.contains(boundary) {
+                            let qend = cursor.next_grapheme(boundary).unwrap_or(boundary);
+                            // A terminator right before a closing bracket ends the
+                            // sentence after it unless the next word continues it, and
+                            // before a closing quote if the language (or the options)
+                            // put punctuation inside quotes
+                            let closes_range = || {
+                                punctuation_inside_quotes
+                                    || (paragraph[..qend].ends_with(CLOSING_BRACKETS)
+                                        && !self
+                                            .continue_in_next_word(paragraph[qend..].trim_start()))
+                            };
+                            if skippable_ranges.ends_right_after(boundary, qend) && closes_range() {
+                                boundary = qend;
+                            } else {
+                                in_range = true;
+                            }
+                        }
+                        // Moving past a closing quote may still leave it inside an outer quote
+                        in_range = in_range || skippable_ranges.contains(boundary);
+                        if in_range {
+                            search.stats.quote_suppressions += 1;
+                            continue;
+                        }
+                        let before_quote = mtch.as_str().ends_with(':') && {
+                            let after_colon = &paragraph[boundary..];
+                            let quote_start =
+                                boundary + after_colon.len() - after_colon.trim_start().len();
+                            skippable_ranges.starts_at(quote_start)
+                        };
+                        let accepts_terminator = match options.split_on_colon_before_quote {
+                            Some(split) if before_quote => split,
+                            // The colon was only added to catch quotations
+                            Some(true) if mtch.as_str() == ":" && !colon_is_terminator => false,
+                            _ => options.accepts_terminator(mtch.as_str(), &paragraph[boundary..]),
+                        };
+                        if !accepts_terminator
+                            || !options.accepts_sentence_start(&paragraph[boundary..])
+                        {
+                            continue;
+                        }
+                        if boundary == mtch.end()
+                            && options
+                                .moves_terminator_forward(mtch.as_str(), &paragraph[boundary..])
+                        {
+                            boundary = mtch.start();
+                        }
+
+                        if accepts_boundary(text, paragraph_offset + boundary, filter) {
+                            search.stats.boundaries_accepted += 1;
+                            boundaries.push(boundary);
+                        }
+                    }
+                    // Line breaks may still add boundaries in between, so wait for them
+                    if !options.newline_is_boundary {
+                        while emitted + 1 < boundaries.len() {
+                            if emit_span(
+                                paragraph,
+                                paragraph_offset,
+                                boundaries[emitted],
+                                boundaries[emitted + 1],
+                                options.trim,
+                                f,
+                            )
+                            .is_break()
+                            {
+                                return;
+                            }
+                            emitted += 1;
+                        }
+                    }
+                }
+            }
+
+            if options.newline_is_boundary {
+                for newline in NEWLINE_REGEX.find_iter(paragraph) {
+                    if accepts_boundary(text, paragraph_offset + newline.end(), filter) {
+                        boundaries.push(newline.end());
+                    }
+                }
+                boundaries.sort_unstable();
+                // Drop boundaries that would only split off a run of whitespace
+                let mut previous = 0;
+                boundaries.retain(|&boundary| {
+                    let keep = boundary == 0 || !paragraph[previous..boundary].trim().is_empty();
+                    if keep {
+                        previous = boundary;
+                    }
+                    keep
+                });
+            }
+
+            boundaries.push(paragraph.len());
+            for pair in boundaries[emitted..].windows(2) {
+                if emit_span(
+                    paragraph,
+                    paragraph_offset,
+                    pair[0],
+                    pair[1],
+                    options.trim,
+                    f,
+                )
+                .is_break()
+                {
+                    return;
+                }
+            }
+            paragraph_offset += paragraph.len();
+        }
+    }
+
+    fn is_punctuation_between_quotes(&self) -> bool {
+        false
+    }
+    fn is_abbreviation(&self, head: &str, tail: &str, separator: &str) -> bool {
+        if self.abbreviation_char() != separator {
             return false;
         }
 
@@ -243,24 +1668,100 @@ pub trait Language {
             return false;
         }
 
-        let normalized_last_word = {
-            let mut out = String::with_capacity(last_word.len());
-            let mut graphemes = last_word.graphemes(false);
-            out.push_str(&graphemes.next().unwrap().to_lowercase());
-            out.extend(graphemes);
-            out
-        };
-        let is_abbrev = self.abbreviations().contains(last_word)
-            || self.abbreviations().contains(normalized_last_word.as_str())
+        // Multi-token abbreviations such as `et al` or `e.g`
+        if self.matches_abbreviation(last_word)
             || self
-                .abbreviations()
-                .contains(last_word.to_lowercase().as_str())
-            || self
-                .abbreviations()
-                .contains(last_word.to_uppercase().as_str());
+                .get_last_two_words(head)
+                .is_some_and(|words| self.matches_abbreviation(words))
+        {
+            return !self.closes_sentence_before(head, tail);
+        }
 
-        is_abbrev
+        // The separator may be internal to a multi-period abbreviation such as `p. ex.`
+        let next_word = match WORD_SPLIT_REGEX.split(tail.trim_start()).next() {
+            Some(word) if !word.is_empty() => word,
+            _ => return false,
+        };
+        self.matches_abbreviation(&format!("{}{}{}", last_word, separator, next_word))
+            || self.matches_abbreviation(&format!("{}{} {}", last_word, separator, next_word))
+    }
+    /// Looks at the whole token before the separator (e.g. `U.S`) and the word after it
+    /// to decide whether an abbreviation closes the sentence.
+    fn closes_sentence_before(&self, head: &str, tail: &str) -> bool {
+        // A word right after the separator continues the token, as in `U.S.A.`
+        if !tail.starts_with(char::is_whitespace) {
+            return false;
+        }
+        let abbreviation = head.rsplit(char::is_whitespace).next().unwrap_or(head);
+        lookahead_word(tail)
+            .is_some_and(|next_word| self.ends_sentence_after_abbreviation(abbreviation, next_word))
     }
+    /// Decides whether an abbreviation that can also close a sentence (e.g. `U.S.`)
+    /// does so, given the word following it.
+    fn ends_sentence_after_abbreviation(&self, _abbreviation: &str, _next_word: &str) -> bool {
+        false
+    }
+    /// Detects personal initials such as the `R.` in `J. R. R. Tolkien`: a single
+    /// uppercase letter before the separator, followed by whitespace and another capital.
+    fn is_initial(&self, head: &str, tail: &str, separator: &str) -> bool {
+        if self.abbreviation_char() != separator {
+            return false;
+        }
+        let last_word = match self.get_lastword(head) {
+            Some(word) => word,
+            None => return false,
+        };
+        let mut letters = last_word.chars();
+        let is_single_capital =
+            letters.next().is_some_and(|c| c.is_uppercase()) && letters.next().is_none();
+        if !is_single_capital || !tail.starts_with(char::is_whitespace) {
+            return false;
+        }
+        tail.trim_start()
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_uppercase())
+            && !self.closes_sentence_before(head, tail)
+    }
+    /// Checks `word` against the abbreviation set, trying the common casings.
+    fn matches_abbreviation(&self, word: &str) -> bool {
+        if self.abbreviations().contains(word) {
+            return true;
+        }
+        // Case mapping never shortens a word, so longer words can't match any casing
+        if word.chars().nth(self.max_abbreviation_chars()).is_some() {
+            return false;
+        }
+        let mut graphemes = word.graphemes(false);
+        let first_grapheme = graphemes.next().unwrap_or("");
+        if first_grapheme.chars().any(|c| c.is_uppercase()) {
+            let mut normalized_word = first_grapheme.to_lowercase();
+            normalized_word.push_str(graphemes.as_str());
+            if self.abbreviations().contains(normalized_word.as_str()) {
+                return true;
+            }
+        }
+        self.abbreviations().contains(word.to_lowercase().as_str())
+            || self.abbreviations().contains(word.to_uppercase().as_str())
+    }
+    /// Returns the last two words of `text` along with the separator between them,
+    /// e.g. `et al` from `Smith et al` or `e.g` from `see e.g`.
+    fn get_last_two_words<'a>(&'a self, text: &'a str) -> Option<&'a str> {
+        let last_word_start = text.len() - last_word(text).len();
+        if last_word_start == 0 || last_word_start == text.len() {
+            return None;
+        }
+        let last_separator_start = text[..last_word_start]
+            .trim_end_matches(is_word_separator)
+            .len();
+        if last_separator_start == 0 {
+            return None;
+        }
+        let start = last_separator_start - last_word(&text[..last_separator_start]).len();
+        Some(&text[start..])
+    }
+    /// Checks whether the last word of `head` together with `!` is a known word such
+    /// as `Yahoo!`.
     fn is_exclamation_word(&self, head: &str, _tail: &str) -> bool {
         let last_word = match self.get_lastword(head) {
             Some(word) => word,
@@ -269,12 +1770,31 @@ pub trait Language {
         self.exclamation_words()
             .contains(format!("{}!", last_word).as_str())
     }
+    /// Checks whether the last word of `head` together with the matched `terminator`
+    /// is a known word such as `Yahoo!` or `Which?`. An `!` is left to
+    /// `is_exclamation_word`.
+    fn ends_with_exclamation_word(&self, head: &str, tail: &str, terminator: &str) -> bool {
+        if terminator == "!" {
+            return self.is_exclamation_word(head, tail);
+        }
+        let last_word = match self.get_lastword(head) {
+            Some(word) => word,
+            None => return false,
+        };
+        self.exclamation_words()
+            .contains(format!("{}{}", last_word, terminator).as_str())
+    }
     fn abbreviation_char(&self) -> &'static str {
         LANGDATA[self.language_code()].abbreviation_char
     }
     fn abbreviations(&self) -> &'static HashSet<&'static str> {
         &(LANGDATA[self.language_code()].abbreviations)
     }
+    /// The length in chars of the longest abbreviation; must be kept in sync
+    /// with `abbreviations` when overriding it.
+    fn max_abbreviation_chars(&self) -> usize {
+        LANGDATA[self.language_code()].max_abbreviation_chars
+    }
     fn exclamation_words(&self) -> &'static HashSet<&'static str> {
         &(LANGDATA[self.language_code()].exclamation_words)
     }
@@ -282,7 +1802,8 @@ pub trait Language {
 
 struct RegexSplitInclusive<'r, 's> {
     matches: Matches<'r, 's>,
-    remaining: &'s str,
+    text: &'s str,
+    position: usize,
     pending_separator: Option<&'s str>,
 }
 
@@ -290,7 +1811,8 @@ impl<'r, 's> RegexSplitInclusive<'r, 's> {
     fn new(reg: &'r Regex, text: &'s str) -> Self {
         Self {
             matches: reg.find_iter(text),
-            remaining: text,
+            text,
+            position: 0,
             pending_separator: None,
         }
     }
@@ -300,25 +1822,26 @@ impl<'r, 's> std::iter::Iterator for RegexSplitInclusive<'r, 's> {
     type Item = &'s str;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let m = match self.matches.next() {
-            Some(m) => m,
+        if let Some(ps) = self.pending_separator.take() {
+            return Some(ps);
+        }
+        match self.matches.next() {
+            Some(m) => {
+                let retval = &self.text[self.position..m.start()];
+                self.position = m.end();
+                self.pending_separator = Some(m.as_str());
+                Some(retval)
+            }
             None => {
-                if let Some(ps) = self.pending_separator.take() {
-                    return Some(ps);
-                }
-                if !self.remaining.is_empty() {
-                    let retval = self.remaining;
-                    self.remaining = "";
-                    return Some(retval);
+                if self.position < self.text.len() {
+                    let retval = &self.text[self.position..];
+                    self.position = self.text.len();
+                    Some(retval)
                 } else {
-                    return None;
+                    None
                 }
             }
-        };
-        let retval = &self.remaining[..m.start()];
-        self.remaining = &self.remaining[m.end()..];
-        self.pending_separator = Some(m.as_str());
-        Some(retval)
+        }
     }
 }
 
@@ -370,25 +1893,1803 @@ mod test {
         Ok(())
     }
     #[test]
-    fn test_it_can_find_zh() -> Result<()> {
-        let sents = segment("zh", "安永已聯繫周怡安親屬，協助辦理簽證相關事宜，周怡安家屬1月1日晚間搭乘東方航空班機抵達上海，他們步入入境大廳時 神情落寞、不發一語。周怡安來自台中，去年剛從元智大學畢業，同年9月加入安永。")?;
+    fn test_is() -> Result<()> {
+        let sents = segment(
+            "is",
+            "Við fórum út kl. 8 í gær. þá var kalt. Það snjóaði t.d. Á Akureyri.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Við fórum út kl. 8 í gær. þá var kalt.",
+                "Það snjóaði t.d. Á Akureyri."
+            ]
+        );
+        let sents = segment("fo", "Eg búgvi í Havn. ørindi eru mong. Hetta er gott.")?;
         assert_eq!(sents.len(), 2);
         Ok(())
     }
     #[test]
-    fn test_regex_split_inclusive() {
-        let single_line: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
-            .split_inclusive("This is just a single sentence")
-            .collect();
-        assert!(!single_line.is_empty());
-        assert!(!single_line[0].is_empty());
-        let two_lines_no_split: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
-            .split_inclusive("First line\nSecond line\n")
-            .collect();
-        assert_eq!(two_lines_no_split.len(), 1);
-        let two_lines_with_split: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
-            .split_inclusive("First line\n\nSecond line")
-            .collect();
-        assert_eq!(two_lines_with_split.len(), 3);
+    fn test_guillemets() -> Result<()> {
+        let sents = segment("fr", "Il a dit : « Allez. » Puis il partit. Fin.")?;
+        assert_eq!(sents, vec!["Il a dit : « Allez. » Puis il partit.", "Fin."]);
+        let sents = segment("fr", "Il a dit : ‹ Allez. › Puis il partit.")?;
+        assert_eq!(sents.len(), 1);
+        let sents = segment("ru", "Он сказал: «Идём. Быстро!» Потом ушёл.")?;
+        assert_eq!(sents.len(), 1);
+        let sents = segment("de", "Er sagte: »Geh. Schnell!« Dann ging er. Sie blieb.")?;
+        assert_eq!(
+            sents,
+            vec!["Er sagte: »Geh. Schnell!«", "Dann ging er.", "Sie blieb."]
+        );
+        let sents = segment("de", "Er sagte: ›Geh.‹ Dann ging er.")?;
+        assert_eq!(sents, vec!["Er sagte: ›Geh.‹", "Dann ging er."]);
+        Ok(())
+    }
+    #[test]
+    fn test_ku() -> Result<()> {
+        let sents = segment(
+            "ku",
+            "Min pirtûk xwend. şev hat û ez razam. Sibê dîsa xwend hwd. Tiştek din.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Min pirtûk xwend. şev hat û ez razam.",
+                "Sibê dîsa xwend hwd. Tiştek din."
+            ]
+        );
+        let sents = segment("ckb", "ئەمە کتێبێکە؟ بەڵێ، ئەمە کتێبە۔ زۆر باشە.")?;
+        assert_eq!(sents.len(), 3);
+        Ok(())
+    }
+    #[test]
+    fn test_az() -> Result<()> {
+        let sents = segment(
+            "az",
+            "Azərbaycan 28. May 1918-ci ildə müstəqil oldu. Bu tarixi gündür.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Azərbaycan 28. May 1918-ci ildə müstəqil oldu.",
+                "Bu tarixi gündür."
+            ]
+        );
+        let sents = segment(
+            "az",
+            "Fizika İnst. Əməkdaşları gəldi. ılıq hava idi. Sonra getdik.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Fizika İnst. Əməkdaşları gəldi. ılıq hava idi.",
+                "Sonra getdik."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_hy() -> Result<()> {
+        let sents = segment("hy", "Նա ծնվել է 1990 թ. Երևանում։ Ո՞ւր ես գնում։")?;
+        assert_eq!(
+            sents,
+            vec!["Նա ծնվել է 1990 թ. Երևանում։", "Ո՞ւր ես գնում։"]
+        );
+        let sents = segment("hy", "Ո՜վ սիրուն աղջիկ։ Դու գնա՛։ Պրն. Պետրոսյանը եկավ։")?;
+        assert_eq!(
+            sents,
+            vec!["Ո՜վ սիրուն աղջիկ։", "Դու գնա՛։", "Պրն. Պետրոսյանը եկավ։"]
+        );
+        // The ASCII colon stands in for `։`, even before a number
+        let sents = segment("hy", "Թեստերը հաջողվեցին: 2-րդ փուլը սկսվեց:")?;
+        assert_eq!(sents, vec!["Թեստերը հաջողվեցին:", "2-րդ փուլը սկսվեց:"]);
+        assert!(is_known_abbreviation("hy", "թ."));
+        assert!(is_known_abbreviation("hy", "Պրն."));
+        Ok(())
+    }
+    #[test]
+    fn test_en_titles() -> Result<()> {
+        for text in [
+            "Mr. Smith went home.",
+            "Mr Smith went home.",
+            "Dr. Watson and Mrs. Hudson.",
+            "Ms. Jones, Prof. Xavier and Mx. Lee came.",
+            "Messrs. Smith and Jones met Mmes. Brown and Green.",
+            "Rev. Green, Fr. Brown and Pres. Lincoln spoke.",
+            "John Smith Jr. and John Smith Sr. are here.",
+        ] {
+            assert_eq!(segment("en", text)?, vec![text]);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_bo() -> Result<()> {
+        let sents = segment("bo", "བཀྲ་ཤིས་བདེ་ལེགས། ཁྱེད་རང་སྐུ་གཟུགས་བདེ་པོ་ཡིན་པས། ང་བདེ་པོ་ཡིན།།")?;
+        assert_eq!(
+            sents,
+            vec!["བཀྲ་ཤིས་བདེ་ལེགས།", "ཁྱེད་རང་སྐུ་གཟུགས་བདེ་པོ་ཡིན་པས།", "ང་བདེ་པོ་ཡིན།།"]
+        );
+        assert_eq!(segment("bo", "བཀྲ་ཤིས་བདེ་ལེགས")?, vec!["བཀྲ་ཤིས་བདེ་ལེགས"]);
+        assert_eq!(resolve_language("bo"), Some("bo"));
+        assert_eq!(resolve_language("dz"), Some("bo"));
+        Ok(())
+    }
+    #[test]
+    fn test_gl() -> Result<()> {
+        let sents = segment(
+            "gl",
+            "Falei co Dr. Castro onte. Vive en Ourense. ás veces vai a Lugo, p.ex. Nadal. Volveu á casa.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Falei co Dr. Castro onte.",
+                "Vive en Ourense. ás veces vai a Lugo, p.ex. Nadal.",
+                "Volveu á casa."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_eu() -> Result<()> {
+        let sents = segment(
+            "eu",
+            "Jn. Etxeberria etorri da. Liburu asko ditu, adib. Obabakoak. Bihar joango da.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Jn. Etxeberria etorri da.",
+                "Liburu asko ditu, adib. Obabakoak.",
+                "Bihar joango da."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_ca() -> Result<()> {
+        let sents = segment(
+            "ca",
+            "LA COL·LECCIÓ DEL MUSEU. Tinc una il·lusió. Visc a l'Av. Diagonal. És a prop d’Av. Roma.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "LA COL·LECCIÓ DEL MUSEU.",
+                "Tinc una il·lusió.",
+                "Visc a l'Av. Diagonal.",
+                "És a prop d’Av. Roma."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_ha() -> Result<()> {
+        let sents = segment(
+            "ha",
+            "Alh. Musa ya zo gida jiya. ƙasar tana da kyau, Dr. Bello ya ce. Yara sun yi wasa.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Alh. Musa ya zo gida jiya. ƙasar tana da kyau, Dr. Bello ya ce.",
+                "Yara sun yi wasa."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_om() -> Result<()> {
+        let sents = segment(
+            "om",
+            "Ob. Tolaa har'a dhufe. Barnoota baay'ee qaba, fkn. Afaan Oromoo. Boru deema.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Ob. Tolaa har'a dhufe.",
+                "Barnoota baay'ee qaba, fkn. Afaan Oromoo.",
+                "Boru deema."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_tl() -> Result<()> {
+        let sents = segment(
+            "tl",
+            "Si G. Rizal ay isinilang sa Calamba. Dumating si Gng. Santos at si Bb. Cruz kahapon. Maraming prutas, hal. mangga, saging, atbp. sa palengke. Salamat po!",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Si G. Rizal ay isinilang sa Calamba.",
+                "Dumating si Gng. Santos at si Bb. Cruz kahapon.",
+                "Maraming prutas, hal. mangga, saging, atbp. sa palengke.",
+                "Salamat po!"
+            ]
+        );
+        assert_eq!(resolve_language("fil"), Some("tl"));
+        Ok(())
+    }
+    #[test]
+    fn test_ru() -> Result<()> {
+        let sents = segment(
+            "ru",
+            "Это важно, т.е. нужно сделать сегодня. Купили хлеб, молоко и т.д. и т.п. на рынке. Хорошо.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Это важно, т.е. нужно сделать сегодня.",
+                "Купили хлеб, молоко и т.д. и т.п. на рынке.",
+                "Хорошо."
+            ]
+        );
+        // An ordinal date, a decimal, and an abbreviation spelled with spaces
+        let sents = segment(
+            "ru",
+            "Встреча 1. января в 3.30 утра. Он пришёл, т. е. вернулся. Жаль.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Встреча 1. января в 3.30 утра.",
+                "Он пришёл, т. е. вернулся.",
+                "Жаль."
+            ]
+        );
+        for abbreviation in ["т.е.", "т.д.", "т.п.", "г.", "напр."] {
+            assert!(is_known_abbreviation("ru", abbreviation));
+        }
+        // A list closed by an abbreviation ends the sentence before a capitalized word
+        let sents = segment("ru", "Были Иванов, Петров и др. Затем начали.")?;
+        assert_eq!(sents, vec!["Были Иванов, Петров и др.", "Затем начали."]);
+        let sents = segment(
+            "ru",
+            "Купили хлеб, молоко и т.д. Потом ушли. Кофе и т.п. тоже.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Купили хлеб, молоко и т.д.",
+                "Потом ушли.",
+                "Кофе и т.п. тоже."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_mt() -> Result<()> {
+        let sents = segment(
+            "mt",
+            "Kellimt lil Dr. Borg. Kien hemm ħafna nies. ħadd ma ġie, eċċ. ftit. Ċensu telaq.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Kellimt lil Dr. Borg.",
+                "Kien hemm ħafna nies. ħadd ma ġie, eċċ. ftit.",
+                "Ċensu telaq."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_it_can_find_zh() -> Result<()> {
+        let sents = segment("zh", "安永已聯繫周怡安親屬，協助辦理簽證相關事宜，周怡安家屬1月1日晚間搭乘東方航空班機抵達上海，他們步入入境大廳時 神情落寞、不發一語。周怡安來自台中，去年剛從元智大學畢業，同年9月加入安永。")?;
+        assert_eq!(sents.len(), 2);
+        Ok(())
+    }
+    #[test]
+    fn test_language_quote_pairs() -> Result<()> {
+        struct Chinese;
+        impl Language for Chinese {
+            fn language_code(&self) -> &'static str {
+                "zh-hans"
+            }
+        }
+        let text = "他说「你好。我们走吧。」然后离开了。";
+        assert_eq!(Chinese.get_skippable_ranges(text), vec![(6, 36)]);
+        let text = "Press 「 to start. Then 」 to stop.";
+        assert!(find_language("en")?.get_skippable_ranges(text).is_empty());
+        assert!(!Chinese.get_skippable_ranges(text).is_empty());
+        assert_eq!(
+            segment("en", text)?,
+            vec!["Press 「 to start.", "Then 」 to stop."]
+        );
+        assert_eq!(
+            segment("ja", "Dr. Smith came. He left.")?,
+            vec!["Dr. Smith came.", "He left."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_cjk_iteration_marks() -> Result<()> {
+        let sents = segment("ja", "わあ！っていうか、すごいね。また明日。")?;
+        assert_eq!(sents, vec!["わあ！っていうか、すごいね。", "また明日。"]);
+        let sents = segment("ja", "人。々の声が聞こえる。ヽ(´ー｀)ノ")?;
+        assert_eq!(sents, vec!["人。々の声が聞こえる。ヽ(´ー｀)ノ"]);
+        let sents = segment("zh", "你好。我很好。")?;
+        assert_eq!(sents, vec!["你好。", "我很好。"]);
+        Ok(())
+    }
+    #[test]
+    fn test_okina_is_not_a_quote() -> Result<()> {
+        let sents = segment("en", "Ua hele au i Hawaiʻi. He nani ka ʻāina.")?;
+        assert_eq!(sents, vec!["Ua hele au i Hawaiʻi.", "He nani ka ʻāina."]);
+        // The ʻokina is often typed as a left single quotation mark
+        let sents = segment("en", "Ua hele au i Hawai‘i. He nani ka ’āina. Aloha ‘oe.")?;
+        assert_eq!(sents.len(), 3);
+        let language = get_language("en").unwrap();
+        assert_eq!(
+            language.get_lastword("Ua hele au i Hawai‘i"),
+            Some("Hawai‘i")
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_multi_token_abbreviations() -> Result<()> {
+        let sents = segment("en", "This was shown by Smith et al. in their paper. Next.")?;
+        assert_eq!(sents.len(), 2);
+        let sents = segment("es", "Según García et al. La hipótesis es correcta.")?;
+        assert_eq!(sents.len(), 1);
+        let sents = segment("en", "Some fruits, e.g. Apples, are red. Others are not.")?;
+        assert_eq!(
+            sents,
+            vec!["Some fruits, e.g. Apples, are red.", "Others are not."]
+        );
+        let sents = segment("en", "See cf. Table 2 for details.")?;
+        assert_eq!(sents.len(), 1);
+        Ok(())
+    }
+    #[test]
+    fn test_multi_period_abbreviations() -> Result<()> {
+        let sents = segment(
+            "en",
+            "Some fruits, e.g. This one, are red. Others, i.e. The green ones, are not.",
+        )?;
+        assert_eq!(sents.len(), 2);
+        let sents = segment(
+            "de",
+            "Wir brauchen Obst, z.B. Äpfel und Birnen. Viele Sorten, z. B. Äpfel, sind rot.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Wir brauchen Obst, z.B. Äpfel und Birnen.",
+                "Viele Sorten, z. B. Äpfel, sind rot."
+            ]
+        );
+        let sents = segment(
+            "fr",
+            "Il faut des fruits, p. ex. Des pommes. Des fruits, c.-à-d. Des pommes, sont bons.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Il faut des fruits, p. ex. Des pommes.",
+                "Des fruits, c.-à-d. Des pommes, sont bons."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_consecutive_abbreviations() -> Result<()> {
+        let sents = segment(
+            "en",
+            "Bring tools, i.e., e.g., hammers, and so on. Many items, e.g., i.e., etc., were sold. Stuff i.e. e.g. This one.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Bring tools, i.e., e.g., hammers, and so on.",
+                "Many items, e.g., i.e., etc., were sold.",
+                "Stuff i.e. e.g. This one."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_segmentation_profiles() -> Result<()> {
+        let text = "First clause; second clause.\nNext line here.World peace? émile came.";
+        let count = |profile: SegmentationProfile| -> Result<usize> {
+            Ok(segment_with_options("en", text, &profile.into())?.len())
+        };
+        assert_eq!(
+            segment_with_options("en", text, &SegmenterOptions::default())?,
+            segment("en", text)?
+        );
+        assert_eq!(count(SegmentationProfile::Conservative)?, 2);
+        assert_eq!(count(SegmentationProfile::Balanced)?, 4);
+        assert_eq!(count(SegmentationProfile::Aggressive)?, 5);
+        let sents = segment_with_options(
+            "en",
+            "Line one\nLine two",
+            &SegmentationProfile::Aggressive.into(),
+        )?;
+        assert_eq!(sents, vec!["Line one\n", "Line two"]);
+        Ok(())
+    }
+    #[test]
+    fn test_never_break_characters() -> Result<()> {
+        // The ano teleia is a semicolon, in both its Greek and its Latin-1 code point
+        for ano_teleia in ['\u{387}', '\u{b7}'] {
+            let text = format!("Ήρθε αργά{} δεν είχε λεωφορείο. Τι έγινε;", ano_teleia);
+            let sents = segment("el", &text)?;
+            assert_eq!(sents.len(), 2);
+            assert!(sents[0].contains(ano_teleia));
+        }
+        // `՝` is a comma, and `՞` marks a question over the stressed vowel
+        let sents = segment("hy", "Երեկ՝ երեկոյան, նա եկավ։ Ո՞վ էր նա։")?;
+        assert_eq!(sents, vec!["Երեկ՝ երեկոյան, նա եկավ։", "Ո՞վ էր նա։"]);
+        for lang_code in ["el", "hy"] {
+            let language = get_language(lang_code).unwrap();
+            let terminators = language.effective_terminators();
+            assert!(language
+                .never_break_characters()
+                .iter()
+                .all(|c| !terminators.contains(c)));
+        }
+
+        // They win over the terminators the language would otherwise use
+        struct NoSemicolonGreek;
+        impl Language for NoSemicolonGreek {
+            fn language_code(&self) -> &'static str {
+                "el"
+            }
+            fn sentence_break_regex(&self) -> &Regex {
+                get_language("el").unwrap().sentence_break_regex()
+            }
+            fn never_break_characters(&self) -> &'static [char] {
+                &[';']
+            }
+        }
+        let sents = NoSemicolonGreek.segment("Τι έγινε; Δεν ξέρω.");
+        assert_eq!(sents, vec!["Τι έγινε; Δεν ξέρω."]);
+        Ok(())
+    }
+    #[test]
+    fn test_sentence_terminators() -> Result<()> {
+        let sents = segment_detailed("en", "Hi! Bye?")?;
+        let terminators: Vec<Option<char>> = sents.iter().map(|s| s.terminator).collect();
+        assert_eq!(terminators, vec![Some('!'), Some('?')]);
+        let text = "It ended.[2] Goodbye";
+        let sents = segment_detailed("en", text)?;
+        let terminators: Vec<Option<char>> = sents.iter().map(|s| s.terminator).collect();
+        assert_eq!(terminators, vec![Some('.'), None]);
+        for sent in sents {
+            assert_eq!(&text[sent.start..sent.end], sent.text);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_social_text() -> Result<()> {
+        let text = "Great talk by @jane.Doe. Loved it! #rust";
+        assert_eq!(segment("en", text)?.len(), 4);
+        let options = SegmenterOptions {
+            social_text: true,
+            ..Default::default()
+        };
+        let sents = segment_with_options("en", text, &options)?;
+        assert_eq!(
+            sents,
+            vec!["Great talk by @jane.Doe.", "Loved it!", "#rust"]
+        );
+        let sents =
+            segment_with_options("en", "Mail me@host.Io or ping @host.Io. Thanks", &options)?;
+        assert_eq!(sents, vec!["Mail me@host.Io or ping @host.Io.", "Thanks"]);
+        Ok(())
+    }
+    #[test]
+    fn test_initials() -> Result<()> {
+        let sents = segment("en", "J. R. R. Tolkien wrote books. He was British.")?;
+        assert_eq!(
+            sents,
+            vec!["J. R. R. Tolkien wrote books.", "He was British."]
+        );
+        let sents = segment("es", "J. R. R. Tolkien escribió libros. Era británico.")?;
+        assert_eq!(sents.len(), 2);
+        let sents = segment("fr", "Le livre de J. R. R. Tolkien. Fin.")?;
+        assert_eq!(sents, vec!["Le livre de J. R. R. Tolkien.", "Fin."]);
+        Ok(())
+    }
+    #[test]
+    fn test_long_words_before_periods() -> Result<()> {
+        let long_word = "Pneumonoultramicroscopicsilicovolcanoconiosis".repeat(20);
+        let text = format!("This is the word {}. ", long_word).repeat(100);
+        let sents = segment("en", text.trim_end())?;
+        assert_eq!(sents.len(), 100);
+        assert!(sents.iter().all(|sentence| sentence.ends_with("osis.")));
+
+        let language = get_language("en").unwrap();
+        assert!(!language.matches_abbreviation(&long_word));
+        assert!(language.matches_abbreviation("Dr"));
+        assert!(language.matches_abbreviation("DR"));
+        assert!(language.matches_abbreviation("U.S"));
+        Ok(())
+    }
+    #[test]
+    fn test_segment_with_filter() -> Result<()> {
+        let text = "I saw Wham! Live in concert. It was great.";
+        assert_eq!(segment("en", text)?.len(), 3);
+        let sents = segment_with_filter("en", text, |ctx| !ctx.head.ends_with("Wham!"))?;
+        assert_eq!(sents, vec!["I saw Wham! Live in concert.", "It was great."]);
+        let sents = segment_with_filter("en", text, |ctx| {
+            assert_eq!(&text[..ctx.offset], ctx.head);
+            assert_eq!(&text[ctx.offset..], ctx.tail);
+            true
+        })?;
+        assert_eq!(sents.len(), 3);
+        Ok(())
+    }
+    #[test]
+    fn test_unicode_paragraph_separators() -> Result<()> {
+        let sents = segment("en", "Heading\u{2029}Body text. More text.")?;
+        assert_eq!(
+            sents,
+            vec!["Heading", "\u{2029}", "Body text.", "More text."]
+        );
+        let sents = segment("en", "Page one\x0CPage two\u{2028}\nLine")?;
+        assert_eq!(
+            sents,
+            vec!["Page one", "\x0C", "Page two", "\u{2028}\n", "Line"]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_segment_accepts_owned_text() -> Result<()> {
+        let sents = segment("en", String::from("Hi. Bye."))?;
+        assert_eq!(sents, vec!["Hi.", "Bye."]);
+        let text = String::from("Hi. Bye.");
+        assert_eq!(segment("en", &text)?, sents);
+        assert_eq!(segment("en", std::borrow::Cow::from("Hi. Bye."))?, sents);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_reporting() -> Result<()> {
+        let (sents, resolved) = segment_reporting("ast", "Hola. Adiós.")?;
+        assert_eq!(sents, vec!["Hola.", "Adiós."]);
+        assert_eq!(resolved, "es");
+        assert_eq!(segment_reporting("fr", "Salut.")?.1, "fr");
+        assert_eq!(resolve_language("ast"), Some("es"));
+        assert_eq!(resolve_language("xx-unknown"), Some("en"));
+        Ok(())
+    }
+    #[test]
+    fn test_whitespace_only_input() -> Result<()> {
+        for text in ["", "   ", "\n\n\n", " \n\n \n\n\t"] {
+            assert!(segment("en", text)?.is_empty(), "{:?}", text);
+        }
+        assert_eq!(segment("en", "Hi.   ")?, vec!["Hi."]);
+        assert_eq!(segment("en", "Hi.\n\nBye.")?, vec!["Hi.", "\n\n", "Bye."]);
+        Ok(())
+    }
+    #[test]
+    fn test_no_leading_or_trailing_separators() -> Result<()> {
+        for text in [
+            "\n\nHi. Bye.\n\nAgain.\n\n",
+            "\n\n\n\nHi. Bye.\n\nAgain.\n\n\n",
+            "  \n\nHi. Bye.\n\n\n\nAgain.  ",
+        ] {
+            let sents = segment("en", text)?;
+            assert_eq!(sents.len(), 4, "{:?}", sents);
+            assert_eq!(sents[..2], ["Hi.", "Bye."], "{:?}", text);
+            assert!(sents[2].trim().is_empty());
+            assert_eq!(sents[3].trim(), "Again.");
+            assert_eq!(segment_detailed("en", text)?.len(), 4);
+            assert_eq!(segment_sentences_with_indices_iter("en", text)?.count(), 4);
+        }
+        assert_eq!(segment("en", "\n\nHi.")?, vec!["Hi."]);
+        assert_eq!(segment("en", "Hi.\n\n")?, vec!["Hi."]);
+        Ok(())
+    }
+    #[test]
+    fn test_is_known_abbreviation() {
+        assert!(is_known_abbreviation("en", "Dr."));
+        assert!(is_known_abbreviation("en", "Dr"));
+        assert!(is_known_abbreviation("en", "dr."));
+        assert!(is_known_abbreviation("en", "et al."));
+        assert!(!is_known_abbreviation("en", "Watson."));
+        assert!(!is_known_abbreviation("en", "."));
+        assert!(!is_known_abbreviation("en", ""));
+    }
+    #[test]
+    fn test_abbreviation_lookahead() -> Result<()> {
+        let sents = segment("en", "Growth in the U.S. the economy is strong.")?;
+        assert_eq!(sents, vec!["Growth in the U.S. the economy is strong."]);
+        let sents = segment("en", "Growth slowed in the U.S. The economy is weak.")?;
+        assert_eq!(
+            sents,
+            vec!["Growth slowed in the U.S.", "The economy is weak."]
+        );
+        let sents = segment("en", "He joined the U.S. Army in May.")?;
+        assert_eq!(sents, vec!["He joined the U.S. Army in May."]);
+        let sents = segment("en", "This is Dr. The Great.")?;
+        assert_eq!(sents, vec!["This is Dr. The Great."]);
+        let sents = segment("en", "I visited the U.S.A. last year.")?;
+        assert_eq!(sents, vec!["I visited the U.S.A. last year."]);
+        Ok(())
+    }
+    #[test]
+    fn test_company_suffixes() -> Result<()> {
+        let sents = segment("en", "Acme Inc. announced record profits.")?;
+        assert_eq!(sents, vec!["Acme Inc. announced record profits."]);
+        let sents = segment("en", "Shares of Acme Corp. and Smith & Co. rose.")?;
+        assert_eq!(sents, vec!["Shares of Acme Corp. and Smith & Co. rose."]);
+        let sents = segment("en", "He works at Acme Inc. The CEO said so.")?;
+        assert_eq!(sents, vec!["He works at Acme Inc.", "The CEO said so."]);
+        let sents = segment("en", "The shop is Barnes & Co. It opened in May.")?;
+        assert_eq!(sents, vec!["The shop is Barnes & Co.", "It opened in May."]);
+        let sents = segment("fr", "Acme Inc. a publié ses résultats.")?;
+        assert_eq!(sents, vec!["Acme Inc. a publié ses résultats."]);
+        Ok(())
+    }
+    #[test]
+    fn test_technical_tokens() -> Result<()> {
+        let options = SegmenterOptions {
+            protect_technical_tokens: true,
+            ..Default::default()
+        };
+        let text = "Upgrade to 5.3.RELEASE today. Ping 10.0.0.1.It fails.";
+        assert_eq!(
+            segment("en", text)?,
+            vec![
+                "Upgrade to 5.3.",
+                "RELEASE today.",
+                "Ping 10.0.0.1.",
+                "It fails."
+            ]
+        );
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Upgrade to 5.3.RELEASE today.",
+                "Ping 10.0.0.1.",
+                "It fails."
+            ]
+        );
+        let sents = segment_with_options("en", "Try 2.0.rc1.Then 2.0.Final.", &options)?;
+        assert_eq!(sents, vec!["Try 2.0.rc1.", "Then 2.0.", "Final."]);
+        let sents = segment_with_options("en", "Use v1.2.3. Ping 192.168.0.1. Done.", &options)?;
+        assert_eq!(sents, vec!["Use v1.2.3.", "Ping 192.168.0.1.", "Done."]);
+        Ok(())
+    }
+    #[test]
+    fn test_temperature_units() -> Result<()> {
+        let options = SegmenterOptions {
+            protect_technical_tokens: true,
+            ..Default::default()
+        };
+        for (text, expected) in [
+            ("It was 20 °C. We left.", vec!["It was 20 °C.", "We left."]),
+            ("It was 20°C. We left.", vec!["It was 20°C.", "We left."]),
+            (
+                "The angle was 20°. We left.",
+                vec!["The angle was 20°.", "We left."],
+            ),
+            (
+                "His temperature was 98.6°F in the morning. We left.",
+                vec!["His temperature was 98.6°F in the morning.", "We left."],
+            ),
+            (
+                "It rose to 98.6°F. We left.",
+                vec!["It rose to 98.6°F.", "We left."],
+            ),
+        ] {
+            assert_eq!(segment("en", text)?, expected);
+            assert_eq!(segment_with_options("en", text, &options)?, expected);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_split_on_colon() -> Result<()> {
+        let options = SegmenterOptions {
+            split_on_colon: true,
+            ..Default::default()
+        };
+        let text = "Note: This is important.";
+        assert_eq!(segment("en", text)?, vec![text]);
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["Note:", "This is important."]
+        );
+        for text in [
+            "Meet at 3:30 today.",
+            "The match ended 2:1 for the home team.",
+            "Read this: it helps.",
+            "See https://example.com for details.",
+        ] {
+            assert_eq!(segment_with_options("en", text, &options)?, vec![text]);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_split_on_colon_before_quote() -> Result<()> {
+        let text = "He said: “Go home.” Then he left.";
+        assert_eq!(segment("en", text)?, vec![text]);
+        let split = SegmenterOptions {
+            split_on_colon_before_quote: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &split)?,
+            vec!["He said:", "“Go home.” Then he left."]
+        );
+        let options = SegmenterOptions {
+            punctuation_inside_quotes: Some(true),
+            ..split.clone()
+        };
+        assert_eq!(
+            segment_with_options("en", "He said: \"Go home.\" Then he left.", &options)?,
+            vec!["He said:", "\"Go home.\"", "Then he left."]
+        );
+        // Other colons are left alone
+        for text in ["Note: This is important.", "Meet at 3:30 today."] {
+            assert_eq!(segment_with_options("en", text, &split)?, vec![text]);
+        }
+
+        // Where a colon ends sentences, the quotation can be kept with its introduction
+        let text = "Նա ասաց: «Գնա տուն»: Հետո գնաց:";
+        assert_eq!(
+            segment("hy", text)?,
+            vec!["Նա ասաց:", "«Գնա տուն»:", "Հետո գնաց:"]
+        );
+        let join = SegmenterOptions {
+            split_on_colon_before_quote: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("hy", text, &join)?,
+            vec!["Նա ասաց: «Գնա տուն»:", "Հետո գնաց:"]
+        );
+        let join = SegmenterOptions {
+            split_on_colon: true,
+            ..join
+        };
+        let text = "He said: \"Stop\" Then: Nothing.";
+        assert_eq!(
+            segment_with_options("en", text, &join)?,
+            vec!["He said: \"Stop\" Then:", "Nothing."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_resegment() -> Result<()> {
+        let text =
+            "This is Dr. Watson. Hello!\n\nSecond paragraph. It has two sentences.\n\nThe end.  ";
+        let prev = segment_detailed("en", text)?;
+        let edits = [
+            ("Dr. Watson", "Mr. Holmes"),
+            ("Second", "A new. Second"),
+            (" It has two sentences.", ""),
+            ("!\n\nSecond", "! Second"),
+            ("paragraph.", "paragraph.\n\nInserted paragraph."),
+            ("The end.", "The very end. Really."),
+            ("This is", "Now this is"),
+        ];
+        for (old, new) in edits {
+            let edit_start = text.find(old).unwrap();
+            let edited = text.replacen(old, new, 1);
+            let edit = edit_start..edit_start + new.len();
+            assert_eq!(
+                resegment("en", &edited, edit, &prev)?,
+                segment_detailed("en", &edited)?,
+                "{:?}",
+                edited
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_numeric_ranges() -> Result<()> {
+        let options = SegmenterOptions {
+            split_on_colon: true,
+            ..SegmenterOptions::with_profile(SegmentationProfile::Aggressive)
+        };
+        for text in [
+            "The war lasted from 2020–2021 in the region.",
+            "Office hours are 9–5 on weekdays.",
+            "Pages 10—12 cover it.",
+        ] {
+            assert_eq!(segment("en", text)?, vec![text]);
+            assert_eq!(segment_with_options("en", text, &options)?, vec![text]);
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_for_each_sentence() -> Result<()> {
+        let text = "This is Dr. Watson. Thanks for having me!\n\nHow are you? Fine.";
+        let mut sents = Vec::new();
+        for_each_sentence("en", text, |sentence| sents.push(sentence))?;
+        assert_eq!(sents, segment("en", text)?);
+        let mut count = 0;
+        for_each_sentence("en", " \n\n ", |_| count += 1)?;
+        assert_eq!(count, 0);
+        Ok(())
+    }
+    #[test]
+    fn test_exclamation_words() -> Result<()> {
+        let sents = segment("en", "I searched on Yahoo! for hours. Nothing came up.")?;
+        assert_eq!(
+            sents,
+            vec!["I searched on Yahoo! for hours.", "Nothing came up."]
+        );
+        let sents = segment("en", "I read Which? every month. It helps.")?;
+        assert_eq!(sents, vec!["I read Which? every month.", "It helps."]);
+        let sents = segment("en", "I searched on Yahoo? No, Google.")?;
+        assert_eq!(sents, vec!["I searched on Yahoo?", "No, Google."]);
+
+        // Languages overriding `is_exclamation_word` keep working
+        struct Shows;
+        impl Language for Shows {
+            fn language_code(&self) -> &'static str {
+                "en"
+            }
+            fn is_exclamation_word(&self, head: &str, _tail: &str) -> bool {
+                head.ends_with("Oklahoma")
+            }
+        }
+        let sents = Shows.segment("We saw Oklahoma! at the theatre. Which? was sold out.");
+        assert_eq!(
+            sents,
+            vec!["We saw Oklahoma! at the theatre.", "Which? was sold out."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_cyclic_fallbacks() {
+        let registry = HashMap::from([("en", 1), ("fr", 2)]);
+        let fallbacks = HashMap::from([("a", vec!["b"]), ("b", vec!["a"]), ("c", vec!["a", "fr"])]);
+        let resolve = |code| resolve_fallbacks(code, &registry, &fallbacks, &mut HashSet::new());
+        assert_eq!(resolve("a"), None);
+        assert_eq!(resolve("c"), Some(2));
+        assert_eq!(resolve("unknown"), Some(1));
+    }
+    #[test]
+    fn test_segment_limit() -> Result<()> {
+        let text = (1..=10)
+            .map(|i| format!("This is sentence number {}.", i))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let sents = segment_limit("en", &text, 3)?;
+        assert_eq!(sents, segment("en", &text)?[..3]);
+        assert_eq!(segment_limit("en", &text, 0)?, Vec::<String>::new());
+        assert_eq!(segment_limit("en", &text, 20)?.len(), 10);
+        // Paragraph separators don't count
+        assert_eq!(
+            segment_limit("en", "A.\n\nB.", 2)?,
+            vec!["A.", "\n\n", "B."]
+        );
+        assert_eq!(segment_limit("en", "A.\n\nB. C.", 1)?, vec!["A."]);
+
+        // Segmentation stops at the boundary ending the third sentence
+        let mut stats = SegmentStats::default();
+        let mut n_sentences = 0;
+        find_language("en")?.for_each_span(
+            &text,
+            &SegmenterOptions::default(),
+            &|_| true,
+            &mut stats,
+            &mut |_, _| {
+                n_sentences += 1;
+                if n_sentences < 3 {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            },
+        );
+        assert_eq!(n_sentences, 3);
+        assert_eq!(stats.terminators_examined, 3);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_delimited() -> Result<()> {
+        let line = "42\tThe product works. I like it.\t\tShipping was slow. Dr. Who agreed.";
+        assert_eq!(
+            segment_delimited("en", line, '\t')?,
+            vec![
+                vec!["42"],
+                vec!["The product works.", "I like it."],
+                vec![],
+                vec!["Shipping was slow.", "Dr. Who agreed."]
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_has_boundary() -> Result<()> {
+        assert!(!has_boundary("en", "Dr. Smith")?);
+        assert!(!has_boundary("en", "Dr. Smith. ")?);
+        assert!(has_boundary("en", "Dr. Smith. Hi")?);
+        assert!(has_boundary("en", "First paragraph\n\nSecond paragraph")?);
+        assert!(!has_boundary("en", "")?);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_with_stats() -> Result<()> {
+        let (sents, stats) =
+            segment_with_stats("en", "Dr. Watson is here. He said (hi. there) too")?;
+        assert_eq!(
+            sents,
+            segment("en", "Dr. Watson is here. He said (hi. there) too")?
+        );
+        assert_eq!(
+            stats,
+            SegmentStats {
+                terminators_examined: 3,
+                boundaries_accepted: 1,
+                abbreviation_suppressions: 1,
+                quote_suppressions: 1,
+                numbered_references: 0,
+            }
+        );
+        let (_, stats) = segment_with_stats("en", "It was cited.[1] More text.")?;
+        assert_eq!(stats.numbered_references, 1);
+        assert_eq!(stats.boundaries_accepted, 1);
+        Ok(())
+    }
+    #[test]
+    fn test_collapse_internal_whitespace() -> Result<()> {
+        let options = SegmenterOptions {
+            collapse_internal_whitespace: true,
+            ..Default::default()
+        };
+        let text = "Hello    world.  Bye.";
+        assert_eq!(segment("en", text)?, vec!["Hello    world.", "Bye."]);
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["Hello world.", "Bye."]
+        );
+        let sents = segment_with_options("en", "A \t line\nwrap.\n\nNext.", &options)?;
+        assert_eq!(sents, vec!["A line wrap.", "\n\n", "Next."]);
+        Ok(())
+    }
+    #[test]
+    fn test_attach_numbered_references() -> Result<()> {
+        let options = SegmenterOptions {
+            attach_numbered_references: true,
+            ..Default::default()
+        };
+        let text = "Thus increasing the desire for political reform both in Lancashire and in the country at large.[7][8] This was a serious misdemeanour,[16] encouraging them to declare the assembly illegal as soon as it was announced on 31 July.[17][18] The radicals sought a second opinion on the meeting's legality.";
+        // References after a sentence-final period stay with their sentence either way
+        let expected = vec![
+            "Thus increasing the desire for political reform both in Lancashire and in the country at large.[7][8]",
+            "This was a serious misdemeanour,[16] encouraging them to declare the assembly illegal as soon as it was announced on 31 July.[17][18]",
+            "The radicals sought a second opinion on the meeting's legality.",
+        ];
+        assert_eq!(segment("en", text)?, expected);
+        assert_eq!(segment_with_options("en", text, &options)?, expected);
+        // but only the option lets the next word decide whether the sentence ended
+        let text = "It was described by Dr.[3] Smith in 1850.[4][5] It flowers in May.";
+        assert_eq!(
+            segment("en", text)?,
+            vec![
+                "It was described by Dr.[3]",
+                "Smith in 1850.[4][5]",
+                "It flowers in May."
+            ]
+        );
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "It was described by Dr.[3] Smith in 1850.[4][5]",
+                "It flowers in May."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_strip_references() -> Result<()> {
+        let options = SegmenterOptions {
+            strip_references: true,
+            ..Default::default()
+        };
+        let text = "Thus increasing the desire for political reform both in Lancashire and in the country at large.[7][8] This was a serious misdemeanour,[16] encouraging them to declare the assembly illegal as soon as it was announced on 31 July.[17][18] The radicals sought a second opinion on the meeting's legality.";
+        let sents = segment_with_options("en", text, &options)?;
+        assert_eq!(
+            sents,
+            vec![
+                "Thus increasing the desire for political reform both in Lancashire and in the country at large.",
+                "This was a serious misdemeanour, encouraging them to declare the assembly illegal as soon as it was announced on 31 July.",
+                "The radicals sought a second opinion on the meeting's legality."
+            ]
+        );
+        assert!(sents.iter().all(|sentence| !sentence.contains('[')));
+        Ok(())
+    }
+    #[test]
+    fn test_digit_starts_sentence() -> Result<()> {
+        let options = SegmenterOptions {
+            digit_starts_sentence: true,
+            ..Default::default()
+        };
+        let text = "Ich habe 3 Äpfel gekauft. 2 davon sind rot. Pi ist 3.14 und nicht 3.";
+        assert_eq!(
+            segment("de", text)?,
+            vec![
+                "Ich habe 3 Äpfel gekauft. 2 davon sind rot.",
+                "Pi ist 3.14 und nicht 3."
+            ]
+        );
+        assert_eq!(
+            segment_with_options("de", text, &options)?,
+            vec![
+                "Ich habe 3 Äpfel gekauft.",
+                "2 davon sind rot.",
+                "Pi ist 3.14 und nicht 3."
+            ]
+        );
+        // English already lets a number start a sentence
+        let text = "I have 3. 4 of them are mine.";
+        assert_eq!(
+            segment("en", text)?,
+            vec!["I have 3.", "4 of them are mine."]
+        );
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            segment("en", text)?
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_ellipsis_starts_sentence() -> Result<()> {
+        let options = SegmenterOptions {
+            ellipsis_starts_sentence: true,
+            ..Default::default()
+        };
+        let text = "Wait... really? I thought... Never mind. It ended...";
+        assert_eq!(
+            segment("en", text)?,
+            vec![
+                "Wait...",
+                "really?",
+                "I thought...",
+                "Never mind.",
+                "It ended..."
+            ]
+        );
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Wait",
+                "... really?",
+                "I thought",
+                "... Never mind.",
+                "It ended..."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_capitalized_words() -> Result<()> {
+        let conservative = SegmenterOptions::with_profile(SegmentationProfile::Conservative);
+        let with_brands = SegmenterOptions {
+            capitalized_words: ["iOS", "eBay"].map(String::from).into(),
+            ..conservative.clone()
+        };
+        let text = "I bought the new iPhone. iOS is great.";
+        assert_eq!(segment_with_options("en", text, &conservative)?, vec![text]);
+        assert_eq!(
+            segment_with_options("en", text, &with_brands)?,
+            vec!["I bought the new iPhone.", "iOS is great."]
+        );
+        let text = "Ich kaufte es gestern. eBay war billiger.";
+        assert_eq!(segment("de", text)?, vec![text]);
+        assert_eq!(
+            segment_with_options("de", text, &with_brands)?,
+            vec!["Ich kaufte es gestern.", "eBay war billiger."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_abbreviation_at_paragraph_start() -> Result<()> {
+        let sents = segment("en", "Dr. Watson arrived. He sat.")?;
+        assert_eq!(sents, vec!["Dr. Watson arrived.", "He sat."]);
+        let sents = segment("en", "Hello.\n\nDr. Watson arrived.\n\n  Mrs. Hudson left.")?;
+        assert_eq!(
+            sents,
+            vec![
+                "Hello.",
+                "\n\n",
+                "Dr. Watson arrived.",
+                "\n\n",
+                "Mrs. Hudson left."
+            ]
+        );
+        let options = SegmenterOptions::with_profile(SegmentationProfile::Aggressive);
+        let sents = segment_with_options("en", "Hello.\nDr. Watson arrived.", &options)?;
+        assert_eq!(sents, vec!["Hello.", "\nDr. Watson arrived."]);
+        assert_eq!(segment("en", "Dr.")?, vec!["Dr."]);
+        assert_eq!(segment("en", "e.g. this one.")?, vec!["e.g. this one."]);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_document() -> Result<()> {
+        let text = "First one. Second one!\n\n\nNew paragraph.\n\n   \n\nLast? Yes.\n";
+        let document = segment_document("en", text)?;
+        let sentences: Vec<Vec<&str>> = document
+            .paragraphs
+            .iter()
+            .map(|p| p.sentences.iter().map(|s| s.text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            sentences,
+            vec![
+                vec!["First one.", "Second one!"],
+                vec!["New paragraph."],
+                vec!["Last?", "Yes."],
+            ]
+        );
+        // Only whitespace is left between the paragraphs
+        let mut rebuilt = String::new();
+        let mut end = 0;
+        for paragraph in &document.paragraphs {
+            let (start, next_end) = paragraph.span;
+            assert!(text[end..start].trim().is_empty());
+            rebuilt.push_str(&text[end..next_end]);
+            end = next_end;
+            for sentence in &paragraph.sentences {
+                assert_eq!(&text[sentence.start..sentence.end], sentence.text);
+                assert!(start <= sentence.start && sentence.end <= next_end);
+            }
+        }
+        rebuilt.push_str(&text[end..]);
+        assert_eq!(rebuilt, text);
+        let json = serde_json::to_value(&document)?;
+        assert_eq!(json["paragraphs"][1]["span"], serde_json::json!([25, 39]));
+        Ok(())
+    }
+    #[test]
+    fn test_trailing_abbreviation() -> Result<()> {
+        assert_eq!(
+            segment("en", "I live in the U.S.")?,
+            vec!["I live in the U.S."]
+        );
+        assert_eq!(
+            segment("en", "I live in the U.S.  ")?,
+            vec!["I live in the U.S."]
+        );
+        let sents = segment("en", "Hi there. I live in the U.S.")?;
+        assert_eq!(sents, vec!["Hi there.", "I live in the U.S."]);
+        let sentence = &segment_detailed("en", "I live in the U.S.")?[0];
+        assert_eq!((sentence.end, sentence.terminator), (18, Some('.')));
+        Ok(())
+    }
+    #[test]
+    fn test_ideographic_space() -> Result<()> {
+        let sents = segment("zh", "\u{3000}今天天气很好。\u{3000}我们去公园吧！\u{3000}")?;
+        assert_eq!(sents, vec!["今天天气很好。", "我们去公园吧！"]);
+        let sents = segment("ja", "今日は晴れです。\u{3000}\u{3000}散歩に行きましょう。")?;
+        assert_eq!(sents, vec!["今日は晴れです。", "散歩に行きましょう。"]);
+        let sents = segment("en", "This is Dr.\u{3000}Watson.\u{3000}Thanks!")?;
+        assert_eq!(sents, vec!["This is Dr.\u{3000}Watson.", "Thanks!"]);
+        Ok(())
+    }
+    #[test]
+    fn test_long_token_without_boundaries() -> Result<()> {
+        let blob = "QUJDRA+/".repeat(128 * 1024);
+        let sents = segment("en", &blob)?;
+        assert_eq!(sents, vec![blob.clone()]);
+        let text = format!("Attached: {}. Done.", blob);
+        let sents = segment("en", &text)?;
+        assert_eq!(sents.len(), 2);
+        assert_eq!(sents[1], "Done.");
+        Ok(())
+    }
+    #[test]
+    fn test_many_skippable_ranges() -> Result<()> {
+        let options = SegmenterOptions {
+            punctuation_inside_quotes: Some(true),
+            ..Default::default()
+        };
+        let n = 5000;
+        let text = "“Go. Now.” (See p. 4. Or 5.) ".repeat(n);
+        let sents = segment_with_options("en", &text, &options)?;
+        assert_eq!(sents.len(), 2 * n);
+        assert_eq!(sents[..2], ["“Go. Now.”", "(See p. 4. Or 5.)"]);
+        assert_eq!(sents[2 * n - 1], "(See p. 4. Or 5.)");
+
+        // Past the cap, quotes and brackets no longer keep their sentences together
+        let options = SegmenterOptions {
+            max_skippable_ranges: Some(1),
+            ..Default::default()
+        };
+        let text = "He said “Stop. Now.” and (Go. Now) to us.";
+        assert_eq!(segment("en", text)?, vec![text]);
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["He said “Stop. Now.” and (Go.", "Now) to us."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_effective_terminators() -> Result<()> {
+        let el = effective_terminators("el")?;
+        assert!(el.contains(&';') && el.contains(&'.'));
+        assert!(!effective_terminators("en")?.contains(&';'));
+        let hy = effective_terminators("hy")?;
+        assert!(hy.contains(&'։') && !hy.contains(&'.'));
+        assert!(effective_terminators("bo")?.contains(&'།'));
+        assert_eq!(
+            effective_terminators("en")?.len(),
+            GLOBAL_SENTENCE_TERMINATORS.len()
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_straight_quotes() -> Result<()> {
+        let text = "\"She said \"hello.\" Then left.\"";
+        assert_eq!(segment("en", text)?, vec![text]);
+        let sents = segment("de", "\"Sie sagte \"Hallo.\" Dann ging sie.\" Er winkte.")?;
+        assert_eq!(
+            sents,
+            vec!["\"Sie sagte \"Hallo.\" Dann ging sie.\"", "Er winkte."]
+        );
+        let sents = segment(
+            "de",
+            "Er sagte \"Hallo. Bis bald.\" Dann rief sie \"Tschüss. Komm gut heim.\" Fertig.",
+        )?;
+        assert_eq!(
+            sents,
+            vec![
+                "Er sagte \"Hallo. Bis bald.\"",
+                "Dann rief sie \"Tschüss. Komm gut heim.\"",
+                "Fertig."
+            ]
+        );
+        assert_eq!(
+            straight_quote_ranges("He said\"hi. there\" twice."),
+            vec![(7, 18)]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_contractions() -> Result<()> {
+        assert_eq!(segment("en", "I don't. Stop.")?, vec!["I don't.", "Stop."]);
+        let language = find_language("en")?;
+        assert!(language.get_skippable_ranges("I don't. Stop.").is_empty());
+        // The apostrophes don't close the quote opened before them
+        let sents = segment("en", "Rock 'n roll. I don't. Stop.")?;
+        assert_eq!(sents, vec!["Rock 'n roll.", "I don't.", "Stop."]);
+        let text = "He said 'go. I don't. Stop.' Then he left.";
+        assert_eq!(language.get_skippable_ranges(text), vec![(7, 28)]);
+        let sents = segment("en", "He said ‘go. It’s late.’ Then he left.")?;
+        assert_eq!(sents, vec!["He said ‘go. It’s late.’ Then he left."]);
+        Ok(())
+    }
+    #[test]
+    fn test_postprocess() {
+        struct ShoutingLanguage;
+        impl Language for ShoutingLanguage {
+            fn language_code(&self) -> &'static str {
+                "en"
+            }
+            fn postprocess(&self, sentence: String) -> String {
+                sentence.to_uppercase()
+            }
+        }
+        let sents = ShoutingLanguage.segment("Hello there. How are you?\n\nFine.");
+        assert_eq!(sents, vec!["HELLO THERE.", "HOW ARE YOU?", "\n\n", "FINE."]);
+    }
+    #[test]
+    fn test_legal_references() -> Result<()> {
+        let options = SegmenterOptions {
+            legal_references: true,
+            ..Default::default()
+        };
+        let text = "§ 5. This applies to all tenants.\nArt. 3 states that rent is due monthly.\nSec. 4. Definitions apply.";
+        assert_eq!(
+            segment("en", text)?,
+            vec![
+                "§ 5.",
+                "This applies to all tenants.",
+                "\nArt. 3 states that rent is due monthly.",
+                "\nSec. 4.",
+                "Definitions apply."
+            ]
+        );
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "§ 5. This applies to all tenants.",
+                "\nArt. 3 states that rent is due monthly.",
+                "\nSec. 4. Definitions apply."
+            ]
+        );
+        // A reference that ends a line, or is followed by a capital within a line, is
+        // still the end of its sentence
+        let text = "This is covered by § 12.\nThe court agreed. See Art. 3. It is clear. Under § 3. and § 4. nothing changes.";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "This is covered by § 12.",
+                "\nThe court agreed.",
+                "See Art. 3.",
+                "It is clear.",
+                "Under § 3. and § 4. nothing changes."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_keep_list_markers() -> Result<()> {
+        let options = SegmenterOptions {
+            newline_is_boundary: true,
+            keep_list_markers: true,
+            ..Default::default()
+        };
+        let text = "Einkaufen:\n- Milch.\n- drei Eier.\n- 2 Äpfel\n- Brot";
+        assert_eq!(
+            segment("de", text)?,
+            vec!["Einkaufen:\n- Milch.\n- drei Eier.\n- 2 Äpfel\n- Brot"]
+        );
+        assert_eq!(
+            segment_with_options("de", text, &options)?,
+            vec![
+                "Einkaufen:\n",
+                "- Milch.\n",
+                "- drei Eier.\n",
+                "- 2 Äpfel\n",
+                "- Brot"
+            ]
+        );
+        let text = "Steps:\n1. Open it.  \n2. Close it.";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["Steps:\n", "1. Open it.  \n", "2. Close it."]
+        );
+        // Without `newline_is_boundary`, only the items ending a sentence are split
+        let options = SegmenterOptions {
+            keep_list_markers: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", "Buy:\n- milk.\n- eggs.", &options)?,
+            vec!["Buy:\n- milk.\n", "- eggs."]
+        );
+        let sents = segment_with_options("en", "I have 2. Then 3. Done.", &options)?;
+        assert_eq!(sents, vec!["I have 2.", "Then 3.", "Done."]);
+        Ok(())
+    }
+    #[test]
+    fn test_punctuation_inside_quotes() -> Result<()> {
+        let text = "He said “Go home.” Then he left.";
+        assert_eq!(segment("en", text)?, vec![text]);
+        let options = SegmenterOptions {
+            punctuation_inside_quotes: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["He said “Go home.”", "Then he left."]
+        );
+        let text = "Er sagte „Geh nach Hause.“ Dann ging er.";
+        assert_eq!(segment("de", text)?.len(), 2);
+        let options = SegmenterOptions {
+            punctuation_inside_quotes: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(segment_with_options("de", text, &options)?, vec![text]);
+        Ok(())
+    }
+    #[test]
+    fn test_validate_language_data() {
+        assert_eq!(validate_language_data(LANGDATA_STR), Ok(()));
+        let json = r#"{
+            "xx": {"abbreviation_char": "..", "abbreviations": ["Dr", 7], "exclamation_words": []},
+            "yy": {"abbreviation_char": "."}
+        }"#;
+        assert_eq!(
+            validate_language_data(json),
+            Err(vec![
+                "`xx.abbreviation_char`: expected a single grapheme, found `..`".to_string(),
+                "`xx.abbreviations[1]`: expected a string".to_string(),
+                "`yy.abbreviations`: missing".to_string(),
+                "`yy.exclamation_words`: missing".to_string(),
+            ])
+        );
+        assert!(validate_language_data("[1, 2]").is_err());
+        assert!(validate_language_data("{").unwrap_err()[0].starts_with("invalid JSON"));
+    }
+    #[test]
+    fn test_terminator_before_closing_bracket() -> Result<()> {
+        let sents = segment("en", "(See above.) Next.")?;
+        assert_eq!(sents, vec!["(See above.)", "Next."]);
+        let sents = segment("en", "He left early [as planned.] The rest stayed.")?;
+        assert_eq!(
+            sents,
+            vec!["He left early [as planned.]", "The rest stayed."]
+        );
+        // Not right before the closer, so it doesn't end the sentence
+        let text = "He left (see above. It was late) and came back.";
+        assert_eq!(segment("en", text)?, vec![text]);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_bytes_spans() -> Result<()> {
+        let bytes = "Hello world. Ça va? Oui.\n\nBye.".as_bytes();
+        let sents: Vec<&[u8]> = segment_bytes_spans("fr", bytes)?
+            .into_iter()
+            .map(|(start, end)| &bytes[start..end])
+            .collect();
+        assert_eq!(
+            sents,
+            vec![
+                &b"Hello world."[..],
+                "Ça va?".as_bytes(),
+                b"Oui.",
+                b"\n\n",
+                b"Bye."
+            ]
+        );
+        assert!(segment_bytes_spans("en", b"Invalid \xff bytes.").is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_line_is_paragraph() -> Result<()> {
+        let options = SegmenterOptions {
+            line_is_paragraph: true,
+            ..Default::default()
+        };
+        let text =
+            "id=1 Paid in full. Thanks\nid=2 Refund pending\r\n\nid=3 Closed. See Dr. Lee.\n";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "id=1 Paid in full.",
+                "Thanks",
+                "\n",
+                "id=2 Refund pending",
+                "\r\n\n",
+                "id=3 Closed.",
+                "See Dr. Lee.",
+            ]
+        );
+        // Unlike `newline_is_boundary`, the line breaks become separators
+        let options = SegmenterOptions {
+            newline_is_boundary: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", "one\ntwo", &options)?,
+            vec!["one\n", "two"]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_dividers_as_breaks() -> Result<()> {
+        let text = "First part ends here\n---\nSecond part. More.\n\n* * *\n\nThird";
+        assert_eq!(
+            segment("en", text)?,
+            vec![
+                "First part ends here\n---\nSecond part.",
+                "More.",
+                "\n\n",
+                "* * *",
+                "\n\n",
+                "Third"
+            ]
+        );
+        let options = SegmenterOptions {
+            dividers_as_breaks: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["First part ends here", "Second part.", "More.", "Third"]
+        );
+        let text = "===\nTitle. Text -- with dashes.";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["Title.", "Text -- with dashes."]
+        );
+        // Lines of other symbols are text
+        let text = "Great job\n😀😀😀\nThanks.\n...\nYes.\n\n!!!\n\nDone.";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Great job\n😀😀😀\nThanks.",
+                "\n...",
+                "\nYes.",
+                "\n\n",
+                "!!!",
+                "\n\n",
+                "Done."
+            ]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_sentence_lengths() -> Result<()> {
+        let text = "Hi. Go. This sentence is rather long for a single utterance. Bye.\n\nYes.";
+        let options = SegmenterOptions {
+            min_graphemes: Some(8),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Hi. Go. This sentence is rather long for a single utterance. Bye.",
+                "\n\n",
+                "Yes."
+            ]
+        );
+        let options = SegmenterOptions {
+            max_graphemes: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Hi.",
+                "Go.",
+                "This sentence is",
+                "rather long for a",
+                "single utterance.",
+                "Bye.",
+                "\n\n",
+                "Yes."
+            ]
+        );
+        let options = SegmenterOptions {
+            max_graphemes: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", "Incomprehensibilities abound.", &options)?,
+            vec!["Incomprehensibilities", "abound."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_segment_shared() -> Result<()> {
+        let text = "This is Dr. Watson. Thanks for having me!\n\nHow are you?";
+        let sents = segment_shared("en", text)?;
+        let expected = segment("en", text)?;
+        assert_eq!(sents.iter().map(|s| &**s).collect::<Vec<&str>>(), expected);
+        let shared = sents[0].clone();
+        assert!(Arc::ptr_eq(&shared, &sents[0]));
+        assert_eq!(Arc::strong_count(&sents[0]), 2);
+        Ok(())
+    }
+    #[test]
+    fn test_latex_math() -> Result<()> {
+        let options = SegmenterOptions {
+            latex_math: true,
+            ..Default::default()
+        };
+        let text =
+            r"Let $a.B$ be given. Then \(x.Y\) holds and \[f.G\] too. It costs \$5. Next $3.5$.";
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec![
+                "Let $a.B$ be given.",
+                r"Then \(x.Y\) holds and \[f.G\] too.",
+                r"It costs \$5.",
+                "Next $3.5$."
+            ]
+        );
+        assert!(segment("en", text)?.len() > 4);
+        // Prices are not formulas
+        assert_eq!(
+            segment_with_options("en", "It costs $5. Then $10. Done.", &options)?,
+            vec!["It costs $5.", "Then $10.", "Done."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_clone_box() {
+        #[derive(Clone)]
+        struct CustomLanguage {
+            code: &'static str,
+        }
+        impl Language for CustomLanguage {
+            fn language_code(&self) -> &'static str {
+                self.code
+            }
+        }
+        let languages: Vec<Box<dyn CloneableLanguage + Send + Sync>> = vec![
+            Box::new(CustomLanguage { code: "en" }),
+            languages::DeLanguage.clone_box(),
+        ];
+        let copies = languages.clone();
+        assert_eq!(copies[0].language_code(), "en");
+        assert_eq!(copies[1].language_code(), "de");
+        let text = "This is Dr. Watson. He is here.";
+        assert_eq!(copies[0].segment(text), languages[0].segment(text));
+        assert_eq!(copies[0].segment(text).len(), 2);
+    }
+    #[test]
+    fn test_dedup_adjacent() -> Result<()> {
+        let text = "Welcome back. Welcome back.  Today we talk. Welcome back.";
+        assert_eq!(segment("en", text)?.len(), 4);
+        let options = SegmenterOptions {
+            dedup_adjacent: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            segment_with_options("en", text, &options)?,
+            vec!["Welcome back.", "Today we talk.", "Welcome back."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_terminator_starting_paragraph() -> Result<()> {
+        let sents = segment("en", ".NET is great. I like it.\n\n.NET again.")?;
+        assert_eq!(
+            sents,
+            vec![".NET is great.", "I like it.", "\n\n", ".NET again."]
+        );
+        let sents = segment("en", ". Stray period here. Next.")?;
+        assert_eq!(sents, vec![". Stray period here.", "Next."]);
+        assert_eq!(segment("en", "... and then. Fine.")?.len(), 2);
+        // A sentence of symbols still ends
+        let sents = segment("en", "👍. Great job.")?;
+        assert_eq!(sents, vec!["👍.", "Great job."]);
+        Ok(())
+    }
+    #[test]
+    fn test_segment_with_offsets() -> Result<()> {
+        let text = " Héllo wörld. “Go!” he said.\n\nNew paragraph.  \n";
+        let spans = segment_with_offsets("en", text)?;
+        assert_eq!(
+            spans,
+            vec![
+                (1, 15, "Héllo wörld.".to_string()),
+                (16, 34, "“Go!” he said.".to_string()),
+                (34, 36, "\n\n".to_string()),
+                (36, 50, "New paragraph.".to_string()),
+            ]
+        );
+        // Filling the gaps back in with the skipped whitespace gives the input
+        let mut rebuilt = String::new();
+        for (start, end, sentence) in &spans {
+            assert!(text[rebuilt.len()..*start].trim().is_empty());
+            rebuilt.push_str(&text[rebuilt.len()..*start]);
+            rebuilt.push_str(sentence);
+            assert_eq!(rebuilt.len(), *end);
+        }
+        rebuilt.push_str(&text[rebuilt.len()..]);
+        assert_eq!(rebuilt, text);
+        Ok(())
+    }
+    #[test]
+    fn test_boundary_offsets() -> Result<()> {
+        let text = "  Dr. Smith left. “Why?” he asked.\n\nNobody knew!  Bye.\n";
+        let offsets = boundary_offsets("en", text)?;
+        assert_eq!(offsets[0], 0);
+        assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+        let ends = offsets[1..].iter().copied().chain([text.len()]);
+        let pieces: Vec<&str> = offsets
+            .iter()
+            .zip(ends)
+            .map(|(start, end)| &text[*start..end])
+            .collect();
+        assert_eq!(pieces.concat(), text);
+        let trimmed: Vec<&str> = pieces
+            .iter()
+            .map(|piece| piece.trim_matches(' '))
+            .filter(|piece| !piece.trim().is_empty() || *piece == "\n\n")
+            .collect();
+        assert_eq!(trimmed, segment("en", text)?);
+        assert!(boundary_offsets("en", " \n\n ")?.is_empty());
+        Ok(())
+    }
+    #[test]
+    fn test_sentence_script() -> Result<()> {
+        let sents = segment_detailed("ar", "The meeting ended early. انتهى الاجتماع مبكرا.")?;
+        let scripts: Vec<Option<Script>> = sents.iter().map(|s| s.script).collect();
+        assert_eq!(scripts, vec![Some(Script::Latin), Some(Script::Arabic)]);
+        assert_eq!(segment_detailed("en", "2 + 2 = 4.")?[0].script, None);
+        Ok(())
+    }
+    #[test]
+    fn test_sentence_index() -> Result<()> {
+        let text = "One. Two? Three!\n\nFour. Five.";
+        let sentences = segment_detailed("en", text)?;
+        assert_eq!(sentences.len(), 6);
+        for (i, pair) in sentences.windows(2).enumerate() {
+            assert_eq!((pair[0].index, pair[1].index), (i, i + 1));
+            assert!(pair[0].end <= pair[1].start);
+        }
+        let mut shuffled = sentences.clone();
+        shuffled.reverse();
+        shuffled.sort_by_key(|sentence| sentence.index);
+        assert_eq!(shuffled, sentences);
+
+        let document = segment_document("en", text)?;
+        let indices: Vec<usize> = document
+            .paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.sentences.iter().map(|sentence| sentence.index))
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+        Ok(())
+    }
+    #[test]
+    fn test_ascii_graphemes() -> Result<()> {
+        let text = "Dr. Smith (b. 1970) said: \"Hi!\" It's 3.5 km. Yes?\r\nNo.\tOK";
+        let prefix = &text[..text.find('\r').unwrap()];
+        let (by_byte, by_grapheme) = (GraphemeCursor::new(prefix), GraphemeCursor::indexed(prefix));
+        assert!(by_byte.index.is_none());
+        for pos in 0..=prefix.len() + 1 {
+            if pos < prefix.len() {
+                assert_eq!(by_byte.grapheme_at(pos), by_grapheme.grapheme_at(pos));
+            }
+            assert_eq!(by_byte.next_grapheme(pos), by_grapheme.next_grapheme(pos));
+            assert_eq!(by_byte.prev_grapheme(pos), by_grapheme.prev_grapheme(pos));
+        }
+        // `\r\n` is left to the grapheme segmentation
+        let cursor = GraphemeCursor::new(text);
+        assert!(cursor.index.is_some());
+        assert_eq!(cursor.grapheme_at(prefix.len()), "\r\n");
+        assert_eq!(cursor.next_grapheme(prefix.len()), Some(prefix.len() + 2));
+
+        // An ASCII paragraph and its accented twin split the same way
+        let ascii = "Dr. Smith came at 3 p.m. today. He left (in a hurry). \"Why?\" I asked.";
+        let accented = ascii.replace("Smith", "Smïth").replace("hurry", "hürry");
+        let text = format!("{}\n\n{}", ascii, accented);
+        let sents = segment("en", &text)?;
+        let n = sents.iter().position(|sent| sent == "\n\n").unwrap();
+        assert_eq!(sents[n + 1..].len(), n);
+        for (ascii_sent, accented_sent) in sents[..n].iter().zip(&sents[n + 1..]) {
+            assert_eq!(
+                ascii_sent
+                    .replace("Smith", "Smïth")
+                    .replace("hurry", "hürry"),
+                *accented_sent
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn test_trim_mode() -> Result<()> {
+        let text = "  One. \tTwo.\n\nThree.\t";
+        let with_trim = |trim| {
+            let options = SegmenterOptions {
+                trim,
+                ..Default::default()
+            };
+            segment_with_options("en", text, &options)
+        };
+        assert_eq!(with_trim(TrimMode::Spaces)?, segment("en", text)?);
+        assert_eq!(
+            with_trim(TrimMode::Spaces)?,
+            vec!["One.", "\tTwo.", "\n\n", "Three."]
+        );
+        let sents = with_trim(TrimMode::None)?;
+        assert_eq!(sents, vec!["  One.", " \tTwo.", "\n\n", "Three.", "\t"]);
+        assert_eq!(sents.concat(), text);
+        assert_eq!(
+            with_trim(TrimMode::Whitespace)?,
+            vec!["One.", "Two.", "Three."]
+        );
+        Ok(())
+    }
+    #[test]
+    fn test_regex_split_inclusive() {
+        let single_line: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
+            .split_inclusive("This is just a single sentence")
+            .collect();
+        assert!(!single_line.is_empty());
+        assert!(!single_line[0].is_empty());
+        let two_lines_no_split: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
+            .split_inclusive("First line\nSecond line\n")
+            .collect();
+        assert_eq!(two_lines_no_split.len(), 1);
+        let two_lines_with_split: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
+            .split_inclusive("First line\n\nSecond line")
+            .collect();
+        assert_eq!(two_lines_with_split.len(), 3);
+        let three_paragraphs: Vec<&str> = CONSECUTIVE_NEWLINES_REGEX
+            .split_inclusive("First.\n\nSecond.\n\n\nThird.")
+            .collect();
+        assert_eq!(
+            three_paragraphs,
+            vec!["First.", "\n\n", "Second.", "\n\n\n", "Third."]
+        );
     }
 }
@@ -0,0 +1,165 @@
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Runs of whitespace other than line breaks, except for a lone space
+static HORIZONTAL_WHITESPACE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[^\S\n]{2,}|[^\S\n ]").unwrap());
+/// A word broken over two lines with a hyphen, as in `exam-\nple`
+static LINE_WRAP_HYPHEN_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\S*\p{L})-[^\S\n]*\n[^\S\n]*(\p{Ll})").unwrap());
+
+/// The preprocessing steps applied by `normalize_text`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Remove a leading byte order mark.
+    pub strip_bom: bool,
+    /// Turn `\r\n` and lone `\r` line endings into `\n`.
+    pub normalize_line_endings: bool,
+    /// Apply Unicode canonical composition (NFC).
+    pub nfc: bool,
+    /// Replace each run of spaces and tabs with a single space, keeping line breaks.
+    pub collapse_whitespace: bool,
+    /// Join words hyphenated across a line break, as found in text extracted from PDFs.
+    /// Only a lowercase continuation is joined, and a word that already contains a
+    /// hyphen (`state-of-the-\nart`) is left alone.
+    pub dehyphenate: bool,
+}
+
+impl NormalizeOptions {
+    /// Every normalization step.
+    pub fn all() -> Self {
+        Self {
+            strip_bom: true,
+            normalize_line_endings: true,
+            nfc: true,
+            collapse_whitespace: true,
+            dehyphenate: true,
+        }
+    }
+}
+
+/// Prepares `text` for segmentation, borrowing it when no step changes anything.
+pub fn normalize_text<'a>(text: &'a str, options: &NormalizeOptions) -> Cow<'a, str> {
+    let text = if options.strip_bom {
+        text.strip_prefix('\u{feff}').unwrap_or(text)
+    } else {
+        text
+    };
+    let mut text = Cow::Borrowed(text);
+    if options.normalize_line_endings && text.contains('\r') {
+        text = Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"));
+    }
+    if options.nfc && !is_nfc(&text) {
+        text = Cow::Owned(text.nfc().collect());
+    }
+    if options.dehyphenate && LINE_WRAP_HYPHEN_REGEX.is_match(&text) {
+        text = Cow::Owned(
+            LINE_WRAP_HYPHEN_REGEX
+                .replace_all(&text, |caps: &Captures| {
+                    if caps[1].contains('-') {
+                        caps[0].to_string()
+                    } else {
+                        format!("{}{}", &caps[1], &caps[2])
+                    }
+                })
+                .into_owned(),
+        );
+    }
+    if options.collapse_whitespace && HORIZONTAL_WHITESPACE_REGEX.is_match(&text) {
+        text = Cow::Owned(
+            HORIZONTAL_WHITESPACE_REGEX
+                .replace_all(&text, " ")
+                .into_owned(),
+        );
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nothing_to_normalize() {
+        let text = "Hello world.\nBye.";
+        assert!(matches!(
+            normalize_text(text, &NormalizeOptions::all()),
+            Cow::Borrowed(normalized) if normalized == text
+        ));
+        let text = "\u{feff}Hello\r\n";
+        assert_eq!(normalize_text(text, &NormalizeOptions::default()), text);
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        let options = NormalizeOptions {
+            strip_bom: true,
+            ..Default::default()
+        };
+        let normalized = normalize_text("\u{feff}Hello. Bye.", &options);
+        assert!(matches!(normalized, Cow::Borrowed("Hello. Bye.")));
+        // Only a leading one is a byte order mark
+        assert_eq!(normalize_text("Hello\u{feff}.", &options), "Hello\u{feff}.");
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        let options = NormalizeOptions {
+            normalize_line_endings: true,
+            ..Default::default()
+        };
+        let normalized = normalize_text("One.\r\nTwo.\r\rThree.", &options);
+        assert_eq!(normalized, "One.\nTwo.\n\nThree.");
+    }
+
+    #[test]
+    fn test_nfc() {
+        let options = NormalizeOptions {
+            nfc: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalize_text("Cafe\u{301} au lait.", &options),
+            "Café au lait."
+        );
+    }
+
+    #[test]
+    fn test_collapse_whitespace() {
+        let options = NormalizeOptions {
+            collapse_whitespace: true,
+            ..Default::default()
+        };
+        let normalized = normalize_text("Hello  \t world.\n\nBye\t now.", &options);
+        assert_eq!(normalized, "Hello world.\n\nBye now.");
+    }
+
+    #[test]
+    fn test_dehyphenate() -> anyhow::Result<()> {
+        let options = NormalizeOptions {
+            dehyphenate: true,
+            ..Default::default()
+        };
+        let text = "It was an exam-\nple of care. The next one was an Anglo-\nSaxon text about state-of-the-\nart tools.";
+        let normalized = normalize_text(text, &options);
+        assert_eq!(
+            normalized,
+            "It was an example of care. The next one was an Anglo-\nSaxon text about state-of-the-\nart tools."
+        );
+        let sents = crate::segment("en", normalized)?;
+        assert_eq!(sents[0], "It was an example of care.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_all() -> anyhow::Result<()> {
+        let text = "\u{feff}Cafe\u{301}   closed.\r\n\r\nSee  you.";
+        let normalized = normalize_text(text, &NormalizeOptions::all());
+        assert_eq!(normalized, "Café closed.\n\nSee you.");
+        let sents = crate::segment("en", normalized)?;
+        assert_eq!(sents, vec!["Café closed.", "\n\n", "See you."]);
+        Ok(())
+    }
+}
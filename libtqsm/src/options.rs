@@ -0,0 +1,327 @@
+use crate::{
+    lookahead_word, CLOSING_PUNCTUATION, LEGAL_REFERENCE_REGEX, LIST_MARKER_REGEX, SENTENCE_PADDING,
+};
+use std::collections::HashSet;
+
+/// Bundled trade-offs between over- and under-splitting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SegmentationProfile {
+    /// Keeps more text joined: a boundary needs whitespace after the terminator
+    /// and a sentence may not start with a lowercase letter.
+    Conservative,
+    /// The default behavior.
+    #[default]
+    Balanced,
+    /// Splits more: semicolons and line breaks also end sentences.
+    Aggressive,
+}
+
+/// How much of the whitespace around a sentence is left out of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrimMode {
+    /// Keeps all of it, so that the sentences put back together give the text.
+    None,
+    /// Trims spaces, including the ideographic space.
+    #[default]
+    Spaces,
+    /// Trims all whitespace, including line breaks, so that paragraph separators
+    /// are not returned either.
+    Whitespace,
+}
+
+impl TrimMode {
+    /// Returns the start and end of `piece` once trimmed, relative to it.
+    pub(crate) fn trim(self, piece: &str) -> (usize, usize) {
+        let trimmed_start = match self {
+            TrimMode::None => piece,
+            TrimMode::Spaces => piece.trim_start_matches(SENTENCE_PADDING),
+            TrimMode::Whitespace => piece.trim_start(),
+        };
+        let start = piece.len() - trimmed_start.len();
+        let trimmed = match self {
+            TrimMode::None => trimmed_start,
+            TrimMode::Spaces => trimmed_start.trim_end_matches(SENTENCE_PADDING),
+            TrimMode::Whitespace => trimmed_start.trim_end(),
+        };
+        (start, start + trimmed.len())
+    }
+}
+
+/// Options controlling how text is segmented.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmenterOptions {
+    /// Treat `;` as a sentence terminator.
+    pub split_on_semicolon: bool,
+    /// Treat `:` as a sentence terminator when it introduces a capitalized clause,
+    /// as in `Note: This is important.`
+    pub split_on_colon: bool,
+    /// Whether a colon introducing a quotation ends the sentence, as in `He said:` /
+    /// `“Go.”`. `Some(false)` keeps them together even where a colon otherwise ends
+    /// sentences, and `None` keeps the language's (and `split_on_colon`'s) behavior.
+    pub split_on_colon_before_quote: Option<bool>,
+    /// Treat every line break as a sentence boundary.
+    pub newline_is_boundary: bool,
+    /// Treat every line as a paragraph of its own, as in one-record-per-line formats,
+    /// so that single line breaks are returned as paragraph separators.
+    pub line_is_paragraph: bool,
+    /// Only accept a boundary that is followed by whitespace (or the end of the text).
+    pub require_space_after_terminator: bool,
+    /// Reject a boundary if the next sentence starts with a lowercase letter in any script.
+    pub requires_capital_start: bool,
+    /// Treat `@mentions` and `#hashtags` as atomic tokens, as found in tweets and chat.
+    pub social_text: bool,
+    /// Treat version numbers and IP addresses as atomic tokens, so that only a period
+    /// after them can end a sentence.
+    pub protect_technical_tokens: bool,
+    /// Treat LaTeX math (`$...$`, `$$...$$`, `\(...\)` and `\[...\]`) as atomic tokens,
+    /// as found in academic text. An escaped `\$` doesn't start a formula.
+    pub latex_math: bool,
+    /// Collapse runs of whitespace inside each sentence to a single space,
+    /// as found in text extracted from PDFs or OCR.
+    pub collapse_internal_whitespace: bool,
+    /// Keep numbered references such as `[7][8]` with the sentence they annotate
+    /// without forcing a boundary after them; the text that follows the references
+    /// decides whether the sentence ends there.
+    pub attach_numbered_references: bool,
+    /// Remove numbered references such as `[7][8]` from the returned sentences.
+    /// They still take part in placing the boundaries.
+    pub strip_references: bool,
+    /// Let a number after a terminator and a space start a new sentence, as in
+    /// `I have 3. 2 are red.`, in languages that otherwise read it as a continuation
+    /// (e.g. German ordinals). Decimals such as `3.14` are never split.
+    pub digit_starts_sentence: bool,
+    /// Attach an ellipsis (`...` or `…`) at a boundary to the start of the next
+    /// sentence rather than the end of the previous one, as in `Wait` / `... really?`.
+    pub ellipsis_starts_sentence: bool,
+    /// Keep list markers such as `-`, `*` or `1.` with the item they introduce: the
+    /// period of a numbered marker doesn't end a sentence, and a line starting with
+    /// a marker always starts a new one, as in `- milk.` / `- 3 eggs`.
+    pub keep_list_markers: bool,
+    /// Read the period after a numbered legal reference such as `§ 5`, `Art. 3` or
+    /// `Sec. 4` as part of it, as in `§ 5. This applies.`, when the reference opens its
+    /// line or the next word is lowercase. It still ends a sentence at the end of a line.
+    pub legal_references: bool,
+    /// Whether a terminator right before a closing quote ends the sentence after the
+    /// quote, as in `He said “Go.” Then left.`; `None` keeps the language's default.
+    pub punctuation_inside_quotes: Option<bool>,
+    /// Treat lines made only of three or more rule characters (`-*=_~#•`), such as
+    /// `***` or `- - -`, as section breaks: they end the sentence before them and are
+    /// not returned as sentences.
+    pub dividers_as_breaks: bool,
+    /// Drop a sentence identical, apart from surrounding whitespace, to the one right
+    /// before it, as repeated by OCR or in transcripts.
+    pub dedup_adjacent: bool,
+    /// Merge a sentence shorter than this many graphemes into the sentence after it,
+    /// or the one before it at the end of a paragraph.
+    pub min_graphemes: Option<usize>,
+    /// Split a sentence longer than this many graphemes at whitespace. A single word
+    /// that is longer is kept whole.
+    pub max_graphemes: Option<usize>,
+    /// How much of the whitespace around each sentence is left out of it.
+    pub trim: TrimMode,
+    /// Caps the quotes, brackets, and other protected ranges considered in each
+    /// paragraph, as a guard against adversarial input. Only the first ones are kept,
+    /// so sentences may end inside the ranges past the cap.
+    pub max_skippable_ranges: Option<usize>,
+    /// Words with a lowercase initial, such as `iPhone` or `eBay`, that may start
+    /// a sentence as if they were capitalized.
+    pub capitalized_words: HashSet<String>,
+}
+
+impl SegmenterOptions {
+    pub fn with_profile(profile: SegmentationProfile) -> Self {
+        match profile {
+            SegmentationProfile::Conservative => Self {
+                require_space_after_terminator: true,
+                requires_capital_start: true,
+                ..Self::balanced()
+            },
+            SegmentationProfile::Balanced => Self::balanced(),
+            SegmentationProfile::Aggressive => Self {
+                split_on_semicolon: true,
+                newline_is_boundary: true,
+                ..Self::balanced()
+            },
+        }
+    }
+
+    fn balanced() -> Self {
+        Self {
+            split_on_semicolon: false,
+            split_on_colon: false,
+            split_on_colon_before_quote: None,
+            newline_is_boundary: false,
+            line_is_paragraph: false,
+            require_space_after_terminator: false,
+            requires_capital_start: false,
+            social_text: false,
+            protect_technical_tokens: false,
+            latex_math: false,
+            collapse_internal_whitespace: false,
+            attach_numbered_references: false,
+            strip_references: false,
+            digit_starts_sentence: false,
+            ellipsis_starts_sentence: false,
+            keep_list_markers: false,
+            legal_references: false,
+            punctuation_inside_quotes: None,
+            dividers_as_breaks: false,
+            dedup_adjacent: false,
+            min_graphemes: None,
+            max_graphemes: None,
+            trim: TrimMode::Spaces,
+            max_skippable_ranges: None,
+            capitalized_words: HashSet::new(),
+        }
+    }
+
+    /// Whether `text_after_boundary` starts a new sentence with a number.
+    pub(crate) fn starts_with_number(&self, text_after_boundary: &str) -> bool {
+        self.digit_starts_sentence
+            && text_after_boundary.starts_with(char::is_whitespace)
+            && text_after_boundary
+                .trim_start()
+                .starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    /// Whether the line after a boundary opens a list item.
+    pub(crate) fn starts_list_item(&self, text_after_boundary: &str) -> bool {
+        if !self.keep_list_markers {
+            return false;
+        }
+        let after_spaces =
+            text_after_boundary.trim_start_matches(|c: char| c != '\n' && c.is_whitespace());
+        after_spaces
+            .strip_prefix('\n')
+            .is_some_and(|next_line| LIST_MARKER_REGEX.is_match(next_line))
+    }
+
+    /// Whether `terminator` is the period of a numbered marker such as `1.` or `b.`
+    /// that opens the current line of `head`.
+    pub(crate) fn ends_list_marker(
+        &self,
+        head: &str,
+        terminator: &str,
+        text_after_boundary: &str,
+    ) -> bool {
+        if !self.keep_list_markers || terminator != "." {
+            return false;
+        }
+        let line = &head[head.rfind('\n').map_or(0, |pos| pos + 1)..];
+        let marker = line.trim_start();
+        let is_marker = match marker.len() {
+            1 => marker.chars().all(|c| c.is_ascii_alphanumeric()),
+            2..=3 => marker.chars().all(|c| c.is_ascii_digit()),
+            _ => false,
+        };
+        is_marker && text_after_boundary.starts_with(|c: char| c != '\n' && c.is_whitespace())
+    }
+
+    /// Whether `terminator` is the period of a legal reference such as `§ 5.` that
+    /// ends `head`, rather than the end of a sentence.
+    pub(crate) fn ends_legal_reference(
+        &self,
+        head: &str,
+        terminator: &str,
+        text_after_boundary: &str,
+    ) -> bool {
+        if !self.legal_references || terminator != "." {
+            return false;
+        }
+        let Some(reference) = LEGAL_REFERENCE_REGEX.find(head) else {
+            return false;
+        };
+        let rest_of_line =
+            text_after_boundary.trim_start_matches(|c: char| c != '\n' && c.is_whitespace());
+        if rest_of_line.is_empty() || rest_of_line.starts_with('\n') {
+            return false;
+        }
+        let line_start = head[..reference.start()]
+            .rfind('\n')
+            .map_or(0, |pos| pos + 1);
+        let opens_line = head[line_start..reference.start()].trim().is_empty();
+        opens_line || rest_of_line.starts_with(|c: char| c.is_lowercase() || c.is_ascii_digit())
+    }
+
+    /// Checks the options' constraints on the text following a candidate boundary.
+    pub(crate) fn accepts_sentence_start(&self, text_after_boundary: &str) -> bool {
+        if self.require_space_after_terminator {
+            let after_closers = text_after_boundary.trim_start_matches(CLOSING_PUNCTUATION);
+            if after_closers
+                .chars()
+                .next()
+                .is_some_and(|c| !c.is_whitespace())
+            {
+                return false;
+            }
+        }
+        if self.requires_capital_start
+            && text_after_boundary
+                .chars()
+                .find(|c| c.is_alphanumeric())
+                .is_some_and(|c| c.is_lowercase())
+            && !self.starts_with_capitalized_word(text_after_boundary)
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Returns `true` if the text after a boundary starts with one of `capitalized_words`.
+    pub(crate) fn starts_with_capitalized_word(&self, text_after_boundary: &str) -> bool {
+        !self.capitalized_words.is_empty()
+            && lookahead_word(text_after_boundary)
+                .is_some_and(|word| self.capitalized_words.contains(word))
+    }
+
+    /// Checks a boundary found after `terminator`. With `split_on_colon`, a colon only
+    /// ends a sentence when followed by whitespace and a capital, which leaves times
+    /// (`3:30`), scores (`2:1`), and URLs intact. Languages that end sentences with a
+    /// colon (e.g. Armenian) are otherwise left alone.
+    pub(crate) fn accepts_terminator(&self, terminator: &str, text_after_boundary: &str) -> bool {
+        if !self.split_on_colon || !terminator.ends_with(':') {
+            return true;
+        }
+        text_after_boundary.starts_with(char::is_whitespace)
+            && text_after_boundary
+                .trim_start()
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_uppercase())
+    }
+
+    /// Whether the boundary after `terminator` moves before it, so that it starts the next sentence.
+    pub(crate) fn moves_terminator_forward(
+        &self,
+        terminator: &str,
+        text_after_boundary: &str,
+    ) -> bool {
+        let is_ellipsis = terminator == "…"
+            || (terminator.len() > 1 && terminator.chars().all(|c| c == '.' || c == '…'));
+        self.ellipsis_starts_sentence && is_ellipsis && !text_after_boundary.trim().is_empty()
+    }
+
+    /// Characters that end a sentence in addition to the language's own terminators.
+    pub(crate) fn extra_terminators(&self) -> Vec<char> {
+        let mut terminators = Vec::new();
+        if self.split_on_semicolon {
+            terminators.push(';');
+        }
+        if self.split_on_colon || self.split_on_colon_before_quote == Some(true) {
+            terminators.push(':');
+        }
+        terminators
+    }
+}
+
+impl Default for SegmenterOptions {
+    fn default() -> Self {
+        Self::with_profile(SegmentationProfile::default())
+    }
+}
+
+impl From<SegmentationProfile> for SegmenterOptions {
+    fn from(profile: SegmentationProfile) -> Self {
+        Self::with_profile(profile)
+    }
+}
@@ -0,0 +1,196 @@
+use serde::Serialize;
+
+/// A Unicode script, as far as it matters for choosing fonts or a language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Syriac,
+    Thaana,
+    Nko,
+    Devanagari,
+    Bengali,
+    Gurmukhi,
+    Gujarati,
+    Oriya,
+    Tamil,
+    Telugu,
+    Kannada,
+    Malayalam,
+    Sinhala,
+    Thai,
+    Lao,
+    Tibetan,
+    Myanmar,
+    Georgian,
+    Hangul,
+    Ethiopic,
+    Cherokee,
+    CanadianAboriginal,
+    Khmer,
+    Mongolian,
+    Hiragana,
+    Katakana,
+    Han,
+}
+
+impl Script {
+    /// Whether words are written without spaces between them in this script.
+    pub fn is_unspaced(self) -> bool {
+        matches!(
+            self,
+            Script::Han
+                | Script::Hiragana
+                | Script::Katakana
+                | Script::Thai
+                | Script::Lao
+                | Script::Khmer
+                | Script::Myanmar
+                | Script::Tibetan
+        )
+    }
+}
+
+/// The main blocks of each script, sorted by code point
+const SCRIPT_RANGES: [(char, char, Script); 66] = [
+    ('A', 'Z', Script::Latin),
+    ('a', 'z', Script::Latin),
+    ('\u{aa}', '\u{aa}', Script::Latin),
+    ('\u{ba}', '\u{ba}', Script::Latin),
+    ('\u{c0}', '\u{2af}', Script::Latin),
+    ('\u{370}', '\u{3ff}', Script::Greek),
+    ('\u{400}', '\u{52f}', Script::Cyrillic),
+    ('\u{530}', '\u{58f}', Script::Armenian),
+    ('\u{590}', '\u{5ff}', Script::Hebrew),
+    ('\u{600}', '\u{6ff}', Script::Arabic),
+    ('\u{700}', '\u{74f}', Script::Syriac),
+    ('\u{750}', '\u{77f}', Script::Arabic),
+    ('\u{780}', '\u{7bf}', Script::Thaana),
+    ('\u{7c0}', '\u{7ff}', Script::Nko),
+    ('\u{8a0}', '\u{8ff}', Script::Arabic),
+    ('\u{900}', '\u{97f}', Script::Devanagari),
+    ('\u{980}', '\u{9ff}', Script::Bengali),
+    ('\u{a00}', '\u{a7f}', Script::Gurmukhi),
+    ('\u{a80}', '\u{aff}', Script::Gujarati),
+    ('\u{b00}', '\u{b7f}', Script::Oriya),
+    ('\u{b80}', '\u{bff}', Script::Tamil),
+    ('\u{c00}', '\u{c7f}', Script::Telugu),
+    ('\u{c80}', '\u{cff}', Script::Kannada),
+    ('\u{d00}', '\u{d7f}', Script::Malayalam),
+    ('\u{d80}', '\u{dff}', Script::Sinhala),
+    ('\u{e00}', '\u{e7f}', Script::Thai),
+    ('\u{e80}', '\u{eff}', Script::Lao),
+    ('\u{f00}', '\u{fff}', Script::Tibetan),
+    ('\u{1000}', '\u{109f}', Script::Myanmar),
+    ('\u{10a0}', '\u{10ff}', Script::Georgian),
+    ('\u{1100}', '\u{11ff}', Script::Hangul),
+    ('\u{1200}', '\u{139f}', Script::Ethiopic),
+    ('\u{13a0}', '\u{13ff}', Script::Cherokee),
+    ('\u{1400}', '\u{167f}', Script::CanadianAboriginal),
+    ('\u{1780}', '\u{17ff}', Script::Khmer),
+    ('\u{1800}', '\u{18af}', Script::Mongolian),
+    ('\u{1c80}', '\u{1c8f}', Script::Cyrillic),
+    ('\u{1c90}', '\u{1cbf}', Script::Georgian),
+    ('\u{1d00}', '\u{1dbf}', Script::Latin),
+    ('\u{1e00}', '\u{1eff}', Script::Latin),
+    ('\u{1f00}', '\u{1fff}', Script::Greek),
+    ('\u{2c60}', '\u{2c7f}', Script::Latin),
+    ('\u{2d00}', '\u{2d2f}', Script::Georgian),
+    ('\u{2d80}', '\u{2ddf}', Script::Ethiopic),
+    ('\u{2de0}', '\u{2dff}', Script::Cyrillic),
+    ('\u{2e80}', '\u{2fdf}', Script::Han),
+    ('\u{3005}', '\u{3007}', Script::Han),
+    ('\u{3040}', '\u{309f}', Script::Hiragana),
+    ('\u{30a0}', '\u{30ff}', Script::Katakana),
+    ('\u{3130}', '\u{318f}', Script::Hangul),
+    ('\u{31f0}', '\u{31ff}', Script::Katakana),
+    ('\u{3400}', '\u{4dbf}', Script::Han),
+    ('\u{4e00}', '\u{9fff}', Script::Han),
+    ('\u{a640}', '\u{a69f}', Script::Cyrillic),
+    ('\u{a720}', '\u{a7ff}', Script::Latin),
+    ('\u{ab30}', '\u{ab6f}', Script::Latin),
+    ('\u{ac00}', '\u{d7af}', Script::Hangul),
+    ('\u{f900}', '\u{faff}', Script::Han),
+    ('\u{fb13}', '\u{fb17}', Script::Armenian),
+    ('\u{fb1d}', '\u{fb4f}', Script::Hebrew),
+    ('\u{fb50}', '\u{fdff}', Script::Arabic),
+    ('\u{fe70}', '\u{feff}', Script::Arabic),
+    ('\u{ff21}', '\u{ff3a}', Script::Latin),
+    ('\u{ff41}', '\u{ff5a}', Script::Latin),
+    ('\u{ff66}', '\u{ff9f}', Script::Katakana),
+    ('\u{20000}', '\u{3ffff}', Script::Han),
+];
+
+/// Returns the script of `c`, or `None` for characters shared between scripts
+/// (digits, punctuation, symbols) and for scripts not listed in `Script`.
+fn char_script(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    let idx = SCRIPT_RANGES.partition_point(|(_, end, _)| *end < c);
+    SCRIPT_RANGES
+        .get(idx)
+        .filter(|(start, _, _)| *start <= c)
+        .map(|(_, _, script)| *script)
+}
+
+/// Returns the script most letters of `text` are written in, the earliest one on
+/// a tie, or `None` if `text` has no letters of a known script.
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().filter_map(char_script) {
+        match counts.iter_mut().find(|(counted, _)| *counted == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .fold(
+            None,
+            |dominant: Option<(Script, usize)>, (script, count)| match dominant {
+                Some((_, max_count)) if max_count >= count => dominant,
+                _ => Some((script, count)),
+            },
+        )
+        .map(|(script, _)| script)
+}
+
+/// Whether a line ending in `before` and the line `after` it are both written in
+/// scripts without spaces, so that joining them back needs no space.
+pub(crate) fn joins_without_space(before: &str, after: &str) -> bool {
+    let is_unspaced = |script: Option<Script>| script.is_some_and(Script::is_unspaced);
+    is_unspaced(before.chars().rev().find_map(char_script))
+        && is_unspaced(after.chars().find_map(char_script))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dominant_script() {
+        assert_eq!(dominant_script("Hello, world!"), Some(Script::Latin));
+        assert_eq!(dominant_script("مرحبا بالعالم"), Some(Script::Arabic));
+        assert_eq!(dominant_script("Ёжик в тумане"), Some(Script::Cyrillic));
+        assert_eq!(dominant_script("東京の天気"), Some(Script::Han));
+        assert_eq!(dominant_script("アイスクリーム"), Some(Script::Katakana));
+        // Mostly Greek with a Latin acronym
+        assert_eq!(dominant_script("Η NASA ανακοίνωσε"), Some(Script::Greek));
+        assert_eq!(dominant_script("ab αβ"), Some(Script::Latin));
+        assert_eq!(dominant_script("123 - 456!"), None);
+    }
+
+    #[test]
+    fn test_joins_without_space() {
+        assert!(joins_without_space("東京の天気は、", "晴れです。"));
+        assert!(joins_without_space("วันนี้", "อากาศดี"));
+        assert!(!joins_without_space("東京の天気は", "sunny"));
+        assert!(!joins_without_space("The weather in", "東京"));
+        assert!(!joins_without_space("123", "456"));
+    }
+}
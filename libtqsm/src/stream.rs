@@ -0,0 +1,416 @@
+use crate::script::joins_without_space;
+use crate::{
+    emit_span, find_language, first_unclosed_opener, Language, RegexSplitInclusive,
+    RegexSplitInclusiveTrait, SegmentStats, SegmenterOptions, TrimMode, CONSECUTIVE_NEWLINES_REGEX,
+};
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+/// Segments text arriving one line at a time, e.g. hard-wrapped files.
+///
+/// Consecutive lines are joined with a space (or without one between scripts
+/// written without spaces, such as Chinese or Thai) so that sentences wrapped across
+/// lines come out whole, and blank lines end the current paragraph.
+/// A sentence is only yielded once the text following it has been seen.
+pub fn segment_lines<I>(lang_code: &str, lines: I) -> Result<impl Iterator<Item = String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    segment_lines_with_options(lang_code, lines, SegmenterOptions::default())
+}
+
+/// Like [`segment_lines`], applying `options` to the sentences. With `TrimMode::None`,
+/// the indentation of each line is kept as well.
+pub fn segment_lines_with_options<I>(
+    lang_code: &str,
+    lines: I,
+    options: SegmenterOptions,
+) -> Result<impl Iterator<Item = String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let language = find_language(lang_code)?;
+    Ok(SegmentLines {
+        language,
+        options,
+        lines: lines.into_iter(),
+        buffer: String::new(),
+        last_line_start: 0,
+        ready: VecDeque::new(),
+    })
+}
+
+struct SegmentLines<I> {
+    language: &'static (dyn Language + Send + Sync),
+    options: SegmenterOptions,
+    lines: I,
+    /// The sentence that may still continue on the next line
+    buffer: String,
+    /// Where the last line appended to `buffer` starts
+    last_line_start: usize,
+    ready: VecDeque<String>,
+}
+
+impl<I> SegmentLines<I> {
+    /// Segments the buffered text, keeping the last sentence back if it may continue.
+    fn drain_buffer(&mut self, keep_last: bool) {
+        let mut sentences = self
+            .language
+            .segment_with_options(&self.buffer, &self.options);
+        let pending = if keep_last { sentences.pop() } else { None };
+        self.ready
+            .extend(sentences.into_iter().filter(|s| !s.is_empty()));
+        let pending = pending.unwrap_or_default();
+        // The pending sentence is the end of the buffer
+        let last_line_len = self.buffer.len() - self.last_line_start;
+        self.last_line_start = pending.len().saturating_sub(last_line_len);
+        self.buffer = pending;
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for SegmentLines<I> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sentence) = self.ready.pop_front() {
+                return Some(sentence);
+            }
+            match self.lines.next() {
+                Some(line) => {
+                    if line.trim().is_empty() {
+                        self.drain_buffer(false);
+                        continue;
+                    }
+                    let line = match self.options.trim {
+                        TrimMode::None => line.trim_end(),
+                        _ => line.trim(),
+                    };
+                    let previous_line_start = self.last_line_start;
+                    if !self.buffer.is_empty() && !joins_without_space(&self.buffer, line) {
+                        self.buffer.push(' ');
+                    }
+                    self.last_line_start = self.buffer.len();
+                    self.buffer.push_str(line);
+                    // Boundaries only follow terminators, and those before the previous
+                    // line were already decided with the text after them in view, so
+                    // a long run without terminators isn't segmented over and over
+                    let may_break = self
+                        .buffer
+                        .get(previous_line_start..)
+                        .is_none_or(|recent| self.language.sentence_break_regex().is_match(recent));
+                    if may_break {
+                        self.drain_buffer(true);
+                    }
+                }
+                None => {
+                    if self.buffer.is_empty() {
+                        return None;
+                    }
+                    self.drain_buffer(false);
+                }
+            }
+        }
+    }
+}
+
+/// Starts segmenting text that arrives in arbitrary chunks, e.g. live captions.
+pub fn segment_streaming(lang_code: &str) -> Result<StreamingSegmenter> {
+    Ok(StreamingSegmenter {
+        language: find_language(lang_code)?,
+        buffer: String::new(),
+        last_chunk_start: 0,
+    })
+}
+
+/// Segments text fed in chunks, emitting a sentence only once the boundary after it
+/// is confirmed by the text that follows, so emitted sentences never change.
+/// Sentences from an unclosed bracket or quote on are held back, as its closing
+/// may join them.
+pub struct StreamingSegmenter {
+    language: &'static (dyn Language + Send + Sync),
+    buffer: String,
+    /// Where the last chunk appended to `buffer` starts
+    last_chunk_start: usize,
+}
+
+impl StreamingSegmenter {
+    /// Adds `chunk` to the text, returning the sentences that are now complete.
+    pub fn feed(&mut self, chunk: &str) -> Vec<String> {
+        let previous_chunk_start = self.last_chunk_start;
+        self.last_chunk_start = self.buffer.len();
+        self.buffer.push_str(chunk);
+        // Boundaries only follow terminators, and those before the previous chunk were
+        // already decided with the text after them in view, so a long run without
+        // terminators isn't segmented over and over
+        if !self
+            .language
+            .sentence_break_regex()
+            .is_match(&self.buffer[previous_chunk_start..])
+        {
+            return Vec::new();
+        }
+        let spans = self.language.segment_spans(&self.buffer);
+        // Brackets and quotes only span one paragraph, so only the last one can hold
+        // an opener that closes later
+        let last_paragraph_start = CONSECUTIVE_NEWLINES_REGEX
+            .find_iter(&self.buffer)
+            .last()
+            .map_or(0, |m| m.end());
+        let held_from = first_unclosed_opener(self.language, &self.buffer[last_paragraph_start..])
+            .map_or(self.buffer.len(), |pos| last_paragraph_start + pos);
+        // The last sentence may always continue
+        let n_complete = spans[..spans.len().saturating_sub(1)]
+            .iter()
+            .take_while(|(_, end)| *end <= held_from)
+            .count();
+        let pending_start = match spans.get(n_complete) {
+            Some((start, _)) => *start,
+            None => return Vec::new(),
+        };
+        let sentences = spans[..n_complete]
+            .iter()
+            .map(|(start, end)| self.buffer[*start..*end].to_string())
+            .collect();
+        self.buffer.drain(..pending_start);
+        self.last_chunk_start = self.last_chunk_start.saturating_sub(pending_start);
+        sentences
+    }
+
+    /// Ends the text, returning the sentences held back so far.
+    pub fn finish(self) -> Vec<String> {
+        self.language.segment(&self.buffer)
+    }
+}
+
+/// Lazily yields each sentence of `text` as a slice of it along with its byte range,
+/// segmenting one paragraph at a time so that large documents are never segmented
+/// ahead of what is consumed.
+pub fn segment_sentences_with_indices_iter<'a>(
+    lang_code: &str,
+    text: &'a str,
+) -> Result<impl Iterator<Item = (&'a str, (usize, usize))>> {
+    let language = find_language(lang_code)?;
+    // Paragraph separators only go between sentences, so whitespace around them all
+    // (or only whitespace) makes no sentence
+    let content_start = text.len() - text.trim_start().len();
+    let content = text[content_start..].trim_end();
+    Ok(SentencesWithIndices {
+        language,
+        text,
+        paragraphs: CONSECUTIVE_NEWLINES_REGEX.split_inclusive(content),
+        paragraph_offset: content_start,
+        ready: VecDeque::new(),
+    })
+}
+
+struct SentencesWithIndices<'a> {
+    language: &'static (dyn Language + Send + Sync),
+    text: &'a str,
+    paragraphs: RegexSplitInclusive<'static, 'a>,
+    paragraph_offset: usize,
+    ready: VecDeque<(usize, usize)>,
+}
+
+impl<'a> Iterator for SentencesWithIndices<'a> {
+    type Item = (&'a str, (usize, usize));
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((start, end)) = self.ready.pop_front() {
+                return Some((&self.text[start..end], (start, end)));
+            }
+            let paragraph = self.paragraphs.next()?;
+            let offset = self.paragraph_offset;
+            self.paragraph_offset += paragraph.len();
+            let ready = &mut self.ready;
+            let mut push = |start, end| {
+                ready.push_back((offset + start, offset + end));
+                ControlFlow::Continue(())
+            };
+            if paragraph.trim().is_empty() {
+                // A paragraph separator, which `for_each_span` skips when on its own
+                let _ = emit_span(
+                    paragraph,
+                    0,
+                    0,
+                    paragraph.len(),
+                    TrimMode::default(),
+                    &mut push,
+                );
+            } else {
+                self.language.for_each_span(
+                    paragraph,
+                    &SegmenterOptions::default(),
+                    &|_| true,
+                    &mut SegmentStats::default(),
+                    &mut push,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_segment_lines() -> Result<()> {
+        let sents: Vec<String> = segment_lines(
+            "en",
+            lines(&[
+                "This is a sentence that",
+                "has been wrapped across",
+                "three lines.",
+            ]),
+        )?
+        .collect();
+        assert_eq!(
+            sents,
+            vec!["This is a sentence that has been wrapped across three lines."]
+        );
+
+        let sents: Vec<String> = segment_lines(
+            "en",
+            lines(&[
+                "First sentence. Second one is",
+                "wrapped. I met Dr.",
+                "Watson.",
+                "",
+                "New paragraph",
+            ]),
+        )?
+        .collect();
+        assert_eq!(
+            sents,
+            vec![
+                "First sentence.",
+                "Second one is wrapped.",
+                "I met Dr. Watson.",
+                "New paragraph"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_lines_without_spaces() -> Result<()> {
+        let sents: Vec<String> = segment_lines(
+            "ja",
+            lines(&["東京の天気は、", "晴れです。次は", "大阪です。"]),
+        )?
+        .collect();
+        assert_eq!(sents, vec!["東京の天気は、晴れです。", "次は大阪です。"]);
+        let sents: Vec<String> =
+            segment_lines("ja", lines(&["東京は Tokyo", "と書きます。"]))?.collect();
+        assert_eq!(sents, vec!["東京は Tokyo と書きます。"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_lines_long_run() -> Result<()> {
+        let mut text = vec!["Dr. Watson wrote".to_string()];
+        text.extend((0..20_000).map(|i| format!("line {} of a run without an end", i)));
+        text.push("here. Done.".to_string());
+        let sents: Vec<String> = segment_lines("en", text)?.collect();
+        assert_eq!(sents.len(), 2);
+        assert!(sents[0].starts_with("Dr. Watson wrote line 0 "));
+        assert!(sents[0].ends_with(" line 19999 of a run without an end here."));
+        assert_eq!(sents[1], "Done.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_lines_with_options() -> Result<()> {
+        let options = SegmenterOptions {
+            min_graphemes: Some(8),
+            ..Default::default()
+        };
+        let text = lines(&["Hi. This one is", "wrapped. See you later."]);
+        let sents: Vec<String> = segment_lines_with_options("en", text, options)?.collect();
+        assert_eq!(sents, vec!["Hi. This one is wrapped.", "See you later."]);
+        let options = SegmenterOptions {
+            trim: TrimMode::None,
+            ..Default::default()
+        };
+        let text = lines(&["  One. Two.", "", "Three. "]);
+        let sents: Vec<String> = segment_lines_with_options("en", text, options)?.collect();
+        assert_eq!(sents, vec!["  One.", " Two.", "Three."]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_streaming() -> Result<()> {
+        let mut segmenter = segment_streaming("en")?;
+        assert!(segmenter.feed("Dr. ").is_empty());
+        assert_eq!(segmenter.feed("Smith came. Hi."), vec!["Dr. Smith came."]);
+        assert_eq!(segmenter.feed(" I live in the U.S."), vec!["Hi."]);
+        assert!(segmenter.feed(" the house").is_empty());
+        assert_eq!(segmenter.finish(), vec!["I live in the U.S. the house"]);
+
+        let text = "This is Dr. Watson. Thanks for having me!\n\nHow are you? Fine, e.g. good.";
+        let mut segmenter = segment_streaming("en")?;
+        let mut sents: Vec<String> = text
+            .split_inclusive(|_| true)
+            .flat_map(|chunk| segmenter.feed(chunk))
+            .collect();
+        sents.extend(segmenter.finish());
+        assert_eq!(sents, crate::segment("en", text)?);
+
+        // Nothing is emitted from an open bracket or quote until it closes
+        let mut segmenter = segment_streaming("en")?;
+        assert!(segmenter.feed("He said (go home. Now").is_empty());
+        assert!(segmenter.feed(") ok.").is_empty());
+        assert_eq!(segmenter.finish(), vec!["He said (go home. Now) ok."]);
+        let text = "Hi. She said “Go. Now.” Then (see p. 4. Or 5.) he left. Bye.";
+        for chunk_len in 1..text.len() {
+            let mut segmenter = segment_streaming("en")?;
+            let chars: Vec<char> = text.chars().collect();
+            let mut sents: Vec<String> = chars
+                .chunks(chunk_len)
+                .flat_map(|chunk| segmenter.feed(&String::from_iter(chunk)))
+                .collect();
+            sents.extend(segmenter.finish());
+            assert_eq!(sents, crate::segment("en", text)?);
+        }
+
+        // A long run without terminators isn't segmented again for every chunk
+        let mut segmenter = segment_streaming("en")?;
+        assert!(segmenter.feed("Dr. Watson wrote").is_empty());
+        for i in 0..20_000 {
+            assert!(segmenter.feed(&format!(" word {}", i)).is_empty());
+        }
+        assert_eq!(segmenter.feed(" here. Done").len(), 1);
+        assert_eq!(segmenter.finish(), vec!["Done"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_sentences_with_indices_iter() -> Result<()> {
+        let language = find_language("en")?;
+        for text in [
+            "This is Dr. Watson. Thanks for having me!\n\nHow are you? Fine.\n\n   \n\nBye",
+            "\n\nLeading separator. Trailing one.\n\n",
+            " \n\n ",
+            "",
+        ] {
+            let sents: Vec<(&str, (usize, usize))> =
+                segment_sentences_with_indices_iter("en", text)?.collect();
+            let spans: Vec<(usize, usize)> = sents.iter().map(|(_, span)| *span).collect();
+            assert_eq!(spans, language.segment_spans(text));
+            let mut borrowed = Vec::new();
+            crate::for_each_sentence("en", text, |sentence| borrowed.push(sentence))?;
+            let slices: Vec<&str> = sents.iter().map(|(sentence, _)| *sentence).collect();
+            assert_eq!(slices, borrowed);
+        }
+        let mut sents = segment_sentences_with_indices_iter("en", "One. Two.\n\nThree.")?;
+        assert_eq!(sents.next(), Some(("One.", (0, 4))));
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! Checks `segment` against the labeled fixtures in `tests/fixtures`.
+//!
+//! Each fixture is named after a language code and holds cases separated by blank
+//! lines. Every line of a case is one expected sentence; the input is built by
+//! joining them. Lines starting with `#` are comments.
+
+use std::fs;
+use std::path::Path;
+
+/// Languages written without spaces between sentences.
+const UNSPACED_LANGUAGES: [&str; 2] = ["ja", "zh"];
+
+fn parse_cases(fixture: &str) -> Vec<Vec<&str>> {
+    let mut cases = Vec::new();
+    let mut current = Vec::new();
+    for line in fixture.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                cases.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        cases.push(current);
+    }
+    cases
+}
+
+#[test]
+fn test_fixtures() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut fixture_paths: Vec<_> = fs::read_dir(&fixtures_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    fixture_paths.sort();
+    assert!(!fixture_paths.is_empty(), "No fixtures found");
+
+    let mut failures = Vec::new();
+    for path in fixture_paths {
+        let lang = path.file_stem().unwrap().to_str().unwrap();
+        let separator = if UNSPACED_LANGUAGES.contains(&lang) {
+            ""
+        } else {
+            " "
+        };
+        let fixture = fs::read_to_string(&path).unwrap();
+        for expected in parse_cases(&fixture) {
+            let text = expected.join(separator);
+            let actual = libtqsm::segment(lang, &text).unwrap();
+            if actual != expected {
+                failures.push(format!(
+                    "[{}] {:?}\n  expected: {:?}\n  actual:   {:?}",
+                    lang, text, expected, actual
+                ));
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} case(s) failed:\n{}",
+        failures.len(),
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,31 @@
+use mlua::prelude::*;
+
+/// Segment given text.
+fn segment(_lua: &Lua, (lang_code, text): (String, String)) -> LuaResult<Vec<String>> {
+    libtqsm::segment(&lang_code, text).map_err(|e| LuaError::RuntimeError(e.to_string()))
+}
+
+/// Sentence segmentation.
+#[cfg_attr(feature = "module", mlua::lua_module)]
+pub fn luatqsm(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+    let exports = lua.create_table()?;
+    exports.set("segment", lua.create_function(segment)?)?;
+    Ok(exports)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_segment() -> LuaResult<()> {
+        let lua = Lua::new();
+        lua.globals().set("luatqsm", luatqsm(&lua)?)?;
+        let sents: LuaTable = lua
+            .load(r#"return luatqsm.segment("en", "This is Dr. Watson. Thanks for having me!")"#)
+            .eval()?;
+        let sents: Vec<String> = sents.sequence_values().collect::<LuaResult<_>>()?;
+        assert_eq!(sents, vec!["This is Dr. Watson.", "Thanks for having me!"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+import assert from 'node:assert/strict'
+import { createRequire } from 'node:module'
+import test from 'node:test'
+
+const { segment, segmentAsync } = createRequire(import.meta.url)('../index.node')
+
+test('segment', () => {
+  assert.deepEqual(segment('en', 'This is Dr. Watson. Thanks for having me!'), [
+    'This is Dr. Watson.',
+    'Thanks for having me!',
+  ])
+})
+
+test('segmentAsync', async () => {
+  assert.deepEqual(await segmentAsync('en', 'Hello there. How are you?'), ['Hello there.', 'How are you?'])
+})
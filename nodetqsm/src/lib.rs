@@ -0,0 +1,32 @@
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Segment given text.
+#[napi]
+pub fn segment(lang_code: String, text: String) -> Result<Vec<String>> {
+    libtqsm::segment(&lang_code, text).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+pub struct SegmentTask {
+    lang_code: String,
+    text: String,
+}
+
+impl Task for SegmentTask {
+    type Output = Vec<String>;
+    type JsValue = Vec<String>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        libtqsm::segment(&self.lang_code, &self.text).map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Segment given text on the thread pool, without blocking the event loop.
+#[napi]
+pub fn segment_async(lang_code: String, text: String) -> AsyncTask<SegmentTask> {
+    AsyncTask::new(SegmentTask { lang_code, text })
+}
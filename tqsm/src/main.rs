@@ -1,12 +1,39 @@
 use anyhow::Result;
-use clap::Parser;
-use std::fs::File;
-use std::io::{self, prelude::*};
+use clap::{Parser, ValueEnum};
+use encoding_rs::{Encoding, UTF_8};
+use libtqsm::{SegmentStats, SegmenterOptions};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, prelude::*, BufWriter};
 use std::path::PathBuf;
+use std::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
 
 fn main() -> Result<()> {
+    match run() {
+        // The reader went away (e.g. `tqsm ... | head`), which is not a failure
+        Err(err) if is_broken_pipe(&err) => Ok(()),
+        result => result,
+    }
+}
+
+fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+}
+
+fn run() -> Result<()> {
     let mut args = Cli::parse();
     let language = args.language.clone().unwrap();
+    if let (Some(min_length), Some(max_length)) = (args.min_length, args.max_length) {
+        if min_length > max_length {
+            anyhow::bail!(
+                "`--min-length` ({}) is greater than `--max-length` ({})",
+                min_length,
+                max_length
+            )
+        }
+    }
 
     if args.input_file.is_some() || args.output_file.is_some() {
         if args.interactive {
@@ -18,50 +45,236 @@ fn main() -> Result<()> {
         args.interactive = true;
     }
 
+    // One writer for all the input, so that `--numbered` counts through it
+    let mut output = SentenceWriter::new(&args)?;
     let mut input_text = get_input_text(&args)?;
     if args.interactive {
-        loop {
+        // Until the end of `stdin`, where nothing more is read
+        while !input_text.is_empty() {
             if !input_text.trim().is_empty() {
-                tqsm_main(&language, &args, std::mem::take(&mut input_text))?;
+                timed_tqsm_main(
+                    &language,
+                    &args,
+                    std::mem::take(&mut input_text),
+                    &mut output,
+                )?;
             }
             input_text = get_input_text(&args)?;
         }
     } else {
-        tqsm_main(&language, &args, input_text)?;
+        timed_tqsm_main(&language, &args, input_text, &mut output)?;
     }
+    output.finish()
+}
 
+fn timed_tqsm_main(
+    language: &str,
+    args: &Cli,
+    input_text: String,
+    output: &mut SentenceWriter,
+) -> anyhow::Result<()> {
+    if !args.time {
+        return tqsm_main(language, args, input_text, output).map(|_| ());
+    }
+    let n_chars = input_text.chars().count();
+    let start = Instant::now();
+    let n_sentences = tqsm_main(language, args, input_text, output)?;
+    let seconds = start.elapsed().as_secs_f64();
+    let throughput_seconds = seconds.max(f64::EPSILON);
+    eprintln!(
+        "Segmented {} sentences ({} chars) in {:.6}s: {:.1} sentences/s, {:.1} chars/s",
+        n_sentences,
+        n_chars,
+        seconds,
+        n_sentences as f64 / throughput_seconds,
+        n_chars as f64 / throughput_seconds
+    );
     Ok(())
 }
 
-fn tqsm_main(language: &str, args: &Cli, input_text: String) -> anyhow::Result<()> {
-    let mut sentences: String = String::new();
-    if args.input_file.is_none() {
-        let input = input_text;
-        let sents = libtqsm::segment(language, &input)?.join("\r\n");
-        sentences.push_str(&sents);
-        sentences.push_str("\r\n");
+/// Segments and writes out `input_text`, returning the number of sentences.
+fn tqsm_main(
+    language: &str,
+    args: &Cli,
+    input_text: String,
+    output: &mut SentenceWriter,
+) -> anyhow::Result<usize> {
+    if args.stats_json {
+        return write_stats_json(language, args, &input_text);
+    }
+    let options = args.segmenter_options();
+    let restarts_per_line = args.numbering == Numbering::PerLine;
+    // Each input read from `stdin` is a line of its own
+    if args.input_file.is_none() && restarts_per_line {
+        output.restart_numbering();
+    }
+    if let Some(delimiter) = args.field_delimiter {
+        for (line_index, line) in input_text.lines().enumerate() {
+            if restarts_per_line {
+                output.restart_numbering();
+            }
+            let fields =
+                libtqsm::segment_delimited_with_options(language, line, delimiter, &options)?;
+            for (field_index, field) in fields.into_iter().enumerate() {
+                for sentence in field {
+                    // Locate the sentence, as fields are written out one after another
+                    let located = format!("{}\t{}\t{}", line_index + 1, field_index + 1, sentence);
+                    output.write_sentence(&located)?;
+                }
+            }
+        }
+    } else if args.input_file.is_some() && args.numbered && restarts_per_line {
+        for line in input_text.lines() {
+            output.restart_numbering();
+            for sentence in libtqsm::segment_with_options(language, line, &options)? {
+                output.write_sentence(&sentence)?;
+            }
+        }
+    } else if args.input_file.is_some() {
+        let input_lines = input_text.lines().map(String::from);
+        for sentence in libtqsm::segment_lines_with_options(language, input_lines, options)? {
+            output.write_sentence(&sentence)?;
+        }
+    } else if options == SegmenterOptions::default() {
+        let mut result = Ok(());
+        libtqsm::for_each_sentence(language, &input_text, |sentence| {
+            if result.is_ok() {
+                result = output.write_sentence(sentence);
+            }
+        })?;
+        result?;
     } else {
-        let mut line_sentences = String::new();
-        for input_line in input_text.lines() {
-            let sents = libtqsm::segment(language, input_line)?.join("\r\n");
-            if args.output_file.is_none() {
-                write_to_stdout(&sents)?;
-            } else {
-                line_sentences.push_str(&sents);
-                line_sentences.push_str("\r\n");
+        for sentence in libtqsm::segment_with_options(language, &input_text, &options)? {
+            output.write_sentence(&sentence)?;
+        }
+    }
+    output.end_input()
+}
+
+/// Summary of a segmented text, as written by `--stats-json`.
+#[derive(Serialize)]
+struct StatsReport {
+    language: &'static str,
+    sentences: usize,
+    paragraphs: usize,
+    /// In graphemes
+    average_sentence_length: f64,
+    decisions: SegmentStats,
+}
+
+/// Writes a `StatsReport` of `input_text` as a single line of JSON, returning
+/// the number of sentences.
+fn write_stats_json(language: &str, args: &Cli, input_text: &str) -> anyhow::Result<usize> {
+    let document = libtqsm::segment_document(language, input_text)?;
+    let (_, decisions) = libtqsm::segment_with_stats(language, input_text)?;
+    let sentences = document
+        .paragraphs
+        .iter()
+        .flat_map(|paragraph| &paragraph.sentences);
+    let (n_sentences, n_graphemes) = sentences.fold((0, 0), |(n, total), sentence| {
+        (n + 1, total + sentence.text.graphemes(true).count())
+    });
+    let report = StatsReport {
+        language: libtqsm::resolve_language(language).unwrap_or("en"),
+        sentences: n_sentences,
+        paragraphs: document.paragraphs.len(),
+        average_sentence_length: if n_sentences == 0 {
+            0.0
+        } else {
+            n_graphemes as f64 / n_sentences as f64
+        },
+        decisions,
+    };
+    let json = serde_json::to_string(&report)?;
+    match args.output_file {
+        Some(ref output_filename) => fs::write(output_filename, json + "\n")?,
+        None => writeln!(io::stdout().lock(), "{}", json)?,
+    }
+    Ok(n_sentences)
+}
+
+/// Writes sentences one by one through a buffer, encoding them for the output file.
+struct SentenceWriter {
+    writer: BufWriter<Box<dyn Write>>,
+    encoding: &'static Encoding,
+    separator: &'static str,
+    to_stdout: bool,
+    had_errors: bool,
+    /// Number of sentences written for the current input, paragraph separators aside
+    n_sentences: usize,
+    numbered: bool,
+    /// Number of the last sentence written, for `--numbered`
+    index: usize,
+}
+
+impl SentenceWriter {
+    fn new(args: &Cli) -> anyhow::Result<Self> {
+        let writer: Box<dyn Write> = match args.output_file {
+            Some(ref output_filename) => Box::new(File::create(output_filename)?),
+            None => Box::new(io::stdout().lock()),
+        };
+        let encoding = get_encoding(args.output_encoding.as_deref())?;
+        let to_stdout = args.output_file.is_none();
+        let separator = if to_stdout && args.input_file.is_some() {
+            "\n"
+        } else {
+            "\r\n"
+        };
+        Ok(Self {
+            writer: BufWriter::new(writer),
+            encoding,
+            separator,
+            to_stdout,
+            had_errors: false,
+            n_sentences: 0,
+            numbered: args.numbered,
+            index: 0,
+        })
+    }
+
+    fn write_sentence(&mut self, sentence: &str) -> io::Result<()> {
+        // Paragraph separators are neither counted nor numbered
+        if !sentence.trim().is_empty() {
+            self.n_sentences += 1;
+            if self.numbered {
+                self.index += 1;
+                write!(self.writer, "{}\t", self.index)?;
             }
         }
-        sentences.push_str(&line_sentences);
+        if self.encoding == UTF_8 {
+            self.writer.write_all(sentence.as_bytes())?;
+        } else {
+            let (encoded, _, had_errors) = self.encoding.encode(sentence);
+            self.had_errors |= had_errors;
+            self.writer.write_all(&encoded)?;
+        }
+        self.writer.write_all(self.separator.as_bytes())
     }
 
-    if let Some(ref output_filename) = args.output_file {
-        let mut file = File::create(output_filename)?;
-        file.write_all(sentences.as_bytes())?;
-    } else {
-        write_to_stdout(&sentences)?
+    /// Numbers the next sentence 1 again.
+    fn restart_numbering(&mut self) {
+        self.index = 0;
     }
 
-    Ok(())
+    /// Flushes the sentences of the current input, returning how many were written.
+    fn end_input(&mut self) -> anyhow::Result<usize> {
+        if self.to_stdout {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()?;
+        Ok(std::mem::take(&mut self.n_sentences))
+    }
+
+    /// Reports any characters the output encoding could not represent.
+    fn finish(self) -> anyhow::Result<()> {
+        if self.had_errors {
+            eprintln!(
+                "Warning: some characters could not be represented in `{}`",
+                self.encoding.name()
+            );
+        }
+        Ok(())
+    }
 }
 
 #[derive(Parser)]
@@ -79,21 +292,91 @@ struct Cli {
     /// Use interactive mode (useful for testing)
     #[arg(short, long)]
     interactive: bool,
+    /// Encoding of the input file, e.g. `windows-1256` or `shift_jis` (default `utf-8`)
+    #[arg(short, long, value_name = "NAME")]
+    encoding: Option<String>,
+    /// Encoding of the output, written to a file or `stdout` (default `utf-8`)
+    #[arg(long, value_name = "NAME")]
+    output_encoding: Option<String>,
+    /// Report segmentation time and throughput to `stderr`
+    #[arg(short, long)]
+    time: bool,
+    /// Write segmentation statistics as JSON instead of the sentences
+    #[arg(long)]
+    stats_json: bool,
+    /// Prefix each sentence with its 1-based number and a tab
+    #[arg(long)]
+    numbered: bool,
+    /// Whether `--numbered` counts through the whole input or restarts at every input
+    /// line, segmenting each line on its own
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = Numbering::Continuous)]
+    numbering: Numbering,
+    /// Merge sentences shorter than this many characters into their neighbors
+    #[arg(long, value_name = "N")]
+    min_length: Option<usize>,
+    /// Split sentences longer than this many characters at spaces
+    #[arg(long, value_name = "N")]
+    max_length: Option<usize>,
+    /// How much of the whitespace around each sentence is stripped from it
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = TrimMode::Spaces)]
+    trim_mode: TrimMode,
+    /// Segment each field of every input line on its own, e.g. `\t` for TSV, writing
+    /// each sentence after the numbers of its line and field and a tab
+    #[arg(long, value_name = "CHAR", value_parser = parse_delimiter)]
+    field_delimiter: Option<char>,
 }
 
-fn write_to_stdout(text: &str) -> anyhow::Result<()> {
-    let mut stdout = io::stdout().lock();
-    stdout.write_all(text.as_bytes())?;
-    stdout.write_all(b"\n")?;
-    stdout.flush()?;
-    Ok(())
+impl Cli {
+    /// The options given by `--min-length`, `--max-length` and `--trim-mode`, which
+    /// apply whichever way the input is read.
+    fn segmenter_options(&self) -> SegmenterOptions {
+        SegmenterOptions {
+            min_graphemes: self.min_length,
+            max_graphemes: self.max_length,
+            trim: self.trim_mode.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Numbering {
+    Continuous,
+    PerLine,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TrimMode {
+    None,
+    Spaces,
+    Whitespace,
+}
+
+impl From<TrimMode> for libtqsm::TrimMode {
+    fn from(trim_mode: TrimMode) -> Self {
+        match trim_mode {
+            TrimMode::None => Self::None,
+            TrimMode::Spaces => Self::Spaces,
+            TrimMode::Whitespace => Self::Whitespace,
+        }
+    }
 }
 
 fn get_input_text(args: &Cli) -> anyhow::Result<String> {
     let mut input_buffer = String::new();
     if let Some(ref input_filename) = args.input_file {
         let mut file = File::open(input_filename)?;
-        file.read_to_string(&mut input_buffer)?;
+        let mut input_bytes = Vec::new();
+        file.read_to_end(&mut input_bytes)?;
+        let encoding = get_encoding(args.encoding.as_deref())?;
+        let (decoded, _, had_errors) = encoding.decode(&input_bytes);
+        if had_errors {
+            eprintln!(
+                "Warning: input contains byte sequences that are invalid in `{}`",
+                encoding.name()
+            );
+        }
+        input_buffer.push_str(&decoded);
     } else {
         let stdin = io::stdin();
         stdin.read_line(&mut input_buffer)?;
@@ -101,3 +384,20 @@ fn get_input_text(args: &Cli) -> anyhow::Result<String> {
 
     Ok(input_buffer)
 }
+
+fn parse_delimiter(value: &str) -> Result<char, String> {
+    let value = if value == "\\t" { "\t" } else { value };
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(delimiter), None) => Ok(delimiter),
+        _ => Err("expected a single character or `\\t`".to_string()),
+    }
+}
+
+fn get_encoding(label: Option<&str>) -> anyhow::Result<&'static Encoding> {
+    match label {
+        Some(label) => Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| anyhow::anyhow!("Unknown encoding `{}`", label)),
+        None => Ok(UTF_8),
+    }
+}
@@ -0,0 +1,419 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+fn tqsm() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_tqsm"))
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tqsm-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_windows_1252_input() {
+    let input_file = temp_path("cp1252-input.txt");
+    let output_file = temp_path("cp1252-output.txt");
+    // "Le café est fermé. Très bien." encoded as Windows-1252
+    fs::write(&input_file, b"Le caf\xe9 est ferm\xe9. Tr\xe8s bien.").unwrap();
+
+    let status = tqsm()
+        .args(["-l", "fr", "--encoding", "windows-1252", "-f"])
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&output_file)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = fs::read_to_string(&output_file).unwrap();
+    let sentences: Vec<&str> = output.lines().filter(|l| !l.is_empty()).collect();
+    assert_eq!(sentences, vec!["Le café est fermé.", "Très bien."]);
+
+    fs::remove_file(input_file).unwrap();
+    fs::remove_file(output_file).unwrap();
+}
+
+#[test]
+fn test_output_encoding_on_stdout() {
+    let input_file = temp_path("stdout-encoding-input.txt");
+    fs::write(&input_file, "Le café est fermé. Très bien.").unwrap();
+    let output = tqsm()
+        .args(["-l", "fr", "--output-encoding", "windows-1252", "-f"])
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"Le caf\xe9 est ferm\xe9.\nTr\xe8s bien.\n\n".to_vec()
+    );
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_unknown_encoding() {
+    let input_file = temp_path("unknown-encoding.txt");
+    fs::write(&input_file, "Hello.").unwrap();
+    let output = tqsm()
+        .args(["--encoding", "not-an-encoding", "-f"])
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown encoding"));
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_wrapped_lines_are_joined() {
+    let input_file = temp_path("wrapped-input.txt");
+    fs::write(
+        &input_file,
+        "This sentence is\nwrapped across lines. Short one.\n\nNew paragraph.\n",
+    )
+    .unwrap();
+    let output = tqsm().arg("-f").arg(&input_file).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let sentences: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
+    assert_eq!(
+        sentences,
+        vec![
+            "This sentence is wrapped across lines.",
+            "Short one.",
+            "New paragraph."
+        ]
+    );
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_time_report() {
+    let input_file = temp_path("time-input.txt");
+    fs::write(&input_file, "One sentence. Another one!\n\nA third.\n").unwrap();
+    let output = tqsm()
+        .arg("--time")
+        .arg("-f")
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let report = stderr
+        .lines()
+        .find(|l| l.starts_with("Segmented "))
+        .expect("missing timing report");
+    // Segmented 3 sentences (37 chars) in 0.000012s: 250000.0 sentences/s, 3083333.3 chars/s
+    let words: Vec<&str> = report.split_whitespace().collect();
+    assert_eq!(words[1], "3");
+    assert_eq!(words[2], "sentences");
+    assert_eq!(words[3], "(37");
+    assert!(words[6].trim_end_matches("s:").parse::<f64>().is_ok());
+    assert!(words[7].parse::<f64>().is_ok());
+    assert_eq!(words[8], "sentences/s,");
+    assert!(words[9].parse::<f64>().is_ok());
+    assert_eq!(words[10], "chars/s");
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_large_file_output() {
+    let input_file = temp_path("large-input.txt");
+    let output_file = temp_path("large-output.txt");
+    let input: String = (0..2000)
+        .map(|i| {
+            let paragraph_break = if i % 7 == 0 { "\n" } else { "" };
+            format!(
+                "This is Dr. Watson number {}. Thanks for having me! Is it wrapped\nacross lines? Yes.\n{}",
+                i, paragraph_break
+            )
+        })
+        .collect();
+    fs::write(&input_file, &input).unwrap();
+
+    // Every sentence of the line-joined input, one per line
+    let sentences: Vec<String> = libtqsm::segment_lines("en", input.lines().map(String::from))
+        .unwrap()
+        .collect();
+    let expected_file: String = sentences.iter().map(|s| format!("{}\r\n", s)).collect();
+    let expected_stdout: String = sentences
+        .iter()
+        .map(|s| format!("{}\n", s))
+        .collect::<String>()
+        + "\n";
+
+    let status = tqsm()
+        .arg("-f")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&output_file)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&output_file).unwrap(), expected_file);
+
+    let output = tqsm().arg("-f").arg(&input_file).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected_stdout);
+
+    fs::remove_file(input_file).unwrap();
+    fs::remove_file(output_file).unwrap();
+}
+
+#[test]
+fn test_broken_pipe() {
+    let input_file = temp_path("broken-pipe-input.txt");
+    let input = "This is Dr. Watson. Thanks for having me!\n".repeat(10000);
+    fs::write(&input_file, input).unwrap();
+
+    let mut child = tqsm()
+        .arg("-f")
+        .arg(&input_file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Read one sentence, then close the pipe like `head -n 1` would
+    let mut first_line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut first_line)
+        .unwrap();
+    assert_eq!(first_line, "This is Dr. Watson.\n");
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_stats_json() {
+    let input_file = temp_path("stats-input.txt");
+    fs::write(
+        &input_file,
+        "This is Dr. Watson. Thanks for having me!\n\nHow are you? Fine.\n",
+    )
+    .unwrap();
+    let output = tqsm()
+        .arg("--stats-json")
+        .arg("-f")
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["language"], "en");
+    assert_eq!(stats["sentences"], 4);
+    assert_eq!(stats["paragraphs"], 2);
+    assert_eq!(stats["average_sentence_length"], 14.25);
+    assert_eq!(stats["decisions"]["abbreviation_suppressions"], 1);
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_field_delimiter() {
+    let input_file = temp_path("fields-input.tsv");
+    fs::write(
+        &input_file,
+        "1\tThe product works. I like it.\tShipping was\n2\tslow. Too slow.\tFine.\n",
+    )
+    .unwrap();
+    let output = tqsm()
+        .args(["--field-delimiter", "\\t", "-f"])
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let sentences: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
+    assert_eq!(
+        sentences,
+        vec![
+            "1\t1\t1",
+            "1\t2\tThe product works.",
+            "1\t2\tI like it.",
+            "1\t3\tShipping was",
+            "2\t1\t2",
+            "2\t2\tslow.",
+            "2\t2\tToo slow.",
+            "2\t3\tFine."
+        ]
+    );
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_length_limits() {
+    let input_file = temp_path("lengths-input.txt");
+    fs::write(
+        &input_file,
+        "Hi. This sentence is far too long to be spoken as a single utterance. Yes it is.\n",
+    )
+    .unwrap();
+    let output = tqsm()
+        .args(["--min-length", "5", "--max-length", "30", "-f"])
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let sentences: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
+    assert_eq!(
+        sentences,
+        vec![
+            "Hi. This sentence is far too",
+            "long to be spoken as a single",
+            "utterance.",
+            "Yes it is."
+        ]
+    );
+
+    let output = tqsm()
+        .args(["--min-length", "50", "--max-length", "30", "-f"])
+        .arg(&input_file)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("`--min-length` (50) is greater than `--max-length` (30)"));
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_numbered() {
+    let input_file = temp_path("numbered-input.txt");
+    fs::write(&input_file, "One. Two.\nThree. Four. Five.\n").unwrap();
+    let numbered_lines = |extra_args: &[&str]| -> Vec<String> {
+        let output = tqsm()
+            .arg("--numbered")
+            .args(extra_args)
+            .arg("-f")
+            .arg(&input_file)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(
+        numbered_lines(&[]),
+        vec!["1\tOne.", "2\tTwo.", "3\tThree.", "4\tFour.", "5\tFive."]
+    );
+    assert_eq!(
+        numbered_lines(&["--numbering", "per-line"]),
+        vec!["1\tOne.", "2\tTwo.", "1\tThree.", "2\tFour.", "3\tFive."]
+    );
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_numbered_stdin() {
+    let numbered_lines = |extra_args: &[&str]| -> Vec<String> {
+        let mut child = tqsm()
+            .arg("--numbered")
+            .args(extra_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"One. Two.\nThree.\n")
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(numbered_lines(&[]), vec!["1\tOne.", "2\tTwo.", "3\tThree."]);
+    assert_eq!(
+        numbered_lines(&["--numbering", "per-line"]),
+        vec!["1\tOne.", "2\tTwo.", "1\tThree."]
+    );
+}
+
+#[test]
+fn test_trim_mode() {
+    let input_file = temp_path("trim-input.txt");
+    fs::write(&input_file, "  One. \tTwo.\tThree.").unwrap();
+    let sentences = |trim_mode: &str| -> Vec<String> {
+        let output = tqsm()
+            .args(["--trim-mode", trim_mode, "-f"])
+            .arg(&input_file)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    assert_eq!(sentences("none"), vec!["  One.", " \tTwo.", "\tThree."]);
+    assert_eq!(sentences("spaces"), vec!["One.", "\tTwo.", "\tThree."]);
+    assert_eq!(sentences("whitespace"), vec!["One.", "Two.", "Three."]);
+    fs::remove_file(input_file).unwrap();
+}
+
+#[test]
+fn test_options_combine() {
+    let input_file = temp_path("combined-input.txt");
+    fs::write(
+        &input_file,
+        "Hi. It is\nwrapped.\tYes. No.\nOk. Fine then.\n",
+    )
+    .unwrap();
+    let lines = |extra_args: &[&str]| -> Vec<String> {
+        let output = tqsm()
+            .args(["--min-length", "5"])
+            .args(extra_args)
+            .arg("-f")
+            .arg(&input_file)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect()
+    };
+    // Wrapped lines are still joined
+    assert_eq!(
+        lines(&[]),
+        vec!["Hi. It is wrapped.", "\tYes.", "No. Ok. Fine then."]
+    );
+    assert_eq!(
+        lines(&["--numbered", "--numbering", "per-line"]),
+        vec![
+            "1\tHi. It is",
+            "1\twrapped.",
+            "2\t\tYes. No.",
+            "1\tOk. Fine then."
+        ]
+    );
+    assert_eq!(
+        lines(&["--field-delimiter", "\\t"]),
+        vec![
+            "1\t1\tHi. It is",
+            "2\t1\twrapped.",
+            "2\t2\tYes. No.",
+            "3\t1\tOk. Fine then."
+        ]
+    );
+    fs::remove_file(input_file).unwrap();
+}